@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+use simple_es::blocking::EsClient;
+use simple_es::client::IndexPattern;
+use simple_es::doc::DocId;
+
+#[derive(Serialize, Debug, Clone)]
+struct Data {
+    a: String,
+    b: u16,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requests are sent on its own runtime.
+    let client = EsClient::default();
+
+    let doc = Data {
+        a: "test".to_owned(),
+        b: 5
+    };
+
+    // Index doc into cluster with id.
+    let res1 = client.create_doc::<Data>(
+        IndexPattern::index("test"),
+        DocId::assigned("1"),
+        None,
+        doc,
+    )?;
+
+    // Delete doc just indexed.
+    let res2 = client.delete_doc(
+        IndexPattern::index("test"),
+        DocId::assigned("1")
+    )?;
+
+    println!("{:?}", res1);
+    println!("{:?}", res2);
+
+    Ok(())
+}
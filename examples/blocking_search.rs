@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use simple_es::blocking::EsClient;
+use simple_es::client::IndexPattern;
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Results {
+    a: String,
+    b: u16,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requests are sent on its own runtime.
+    let client = EsClient::default();
+
+    // Return search of all documents in index "test".
+    let res = client.search::<Results>(
+        IndexPattern::index("test"),
+        json!({
+            "query": {
+                "match_all": {}
+            }
+        })
+    )?;
+    println!("{:?}", res);
+
+    Ok(())
+}
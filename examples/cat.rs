@@ -1,19 +1,14 @@
-use tokio::runtime::Runtime;
-
 use simple_es::cat::aliases_req;
 use simple_es::client::EsClient;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup client and runtime.
-    let rt = Runtime::new()?;
-    let client = EsClient::default();
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requesting the version of the cluster on this runtime.
+    let client = EsClient::connect("http://localhost", 9200).await?;
 
     // Print info on aliases.
-    let aliases_future = aliases_req(&client);
-    let info = rt.block_on(aliases_future)?;
+    let info = aliases_req(&client).await?;
     println!("{:?}", info);
 
     Ok(())
 }
-
-
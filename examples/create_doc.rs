@@ -1,4 +1,3 @@
-use tokio::runtime::Runtime;
 use serde::Serialize;
 
 use simple_es::client::{EsClient, IndexPattern};
@@ -10,10 +9,10 @@ struct Data {
     b: u16,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup client and runtime.
-    let rt = Runtime::new()?;
-    let client = EsClient::default();
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requesting the version of the cluster on this runtime.
+    let client = EsClient::connect("http://localhost", 9200).await?;
 
     let doc = Data {
         a: "test".to_owned(),
@@ -21,28 +20,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Index doc into cluster with id.
-    let index_doc_id_future = client.create_doc::<Data>(
-        IndexPattern::Index("test"),
-        DocId::Assigned("1"),
+    let res1 = client.create_doc::<Data>(
+        IndexPattern::index("test"),
+        DocId::assigned("1"),
         None,
         doc.clone(),
-    );
-    
+    ).await?;
+
     // Index doc into cluster without id.
-    let index_doc_no_id_future = client.create_doc::<Data>(
-        IndexPattern::Index("test"),
+    let res2 = client.create_doc::<Data>(
+        IndexPattern::index("test"),
         DocId::Unassigned,
         None,
-        doc.clone(),
-    );
+        doc,
+    ).await?;
 
-    let res1 = rt.block_on(index_doc_id_future)?;
-    let res2 = rt.block_on(index_doc_no_id_future)?;
     println!("{:?}", res1);
     println!("{:?}", res2);
 
     Ok(())
 }
-
-
-
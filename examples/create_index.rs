@@ -1,17 +1,13 @@
-use tokio::runtime::Runtime;
-
 use simple_es::client::EsClient;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup client and runtime.
-    let rt = Runtime::new()?;
-    let client = EsClient::default();
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requesting the version of the cluster on this runtime.
+    let client = EsClient::connect("http://localhost", 9200).await?;
 
     // Create index.
-    let index = rt.block_on(client.create_index("test"))?;
+    let index = client.create_index("test").await?;
     println!("{:?}", index);
 
     Ok(())
 }
-
-
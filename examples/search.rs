@@ -1,34 +1,30 @@
-use tokio::runtime::Runtime;
 use serde::Deserialize;
 use serde_json::json;
 
 use simple_es::client::{EsClient, IndexPattern};
 
 #[derive(Deserialize, Debug)]
+#[allow(dead_code)]
 struct Results {
     a: String,
     b: u16,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup client and runtime.
-    let rt = Runtime::new()?;
-    let client = EsClient::default();
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup client, requesting the version of the cluster on this runtime.
+    let client = EsClient::connect("http://localhost", 9200).await?;
 
     // Return search of all documents in index "test".
-    let search_future = client.search::<Results>(
-        IndexPattern::Index("test"),
+    let res = client.search::<Results>(
+        IndexPattern::index("test"),
         json!({
             "query": {
                 "match_all": {}
             }
         })
-    );
-
-    let res = rt.block_on(search_future)?;
+    ).await?;
     println!("{:?}", res);
 
     Ok(())
 }
-
-
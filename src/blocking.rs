@@ -0,0 +1,344 @@
+//! Blocking EsClient for synchronous code, such as scripts and batch tools, enabled with the
+//! `blocking` feature.
+//!
+//! The client sends requests on its own single threaded runtime, so it must not be used from
+//! within an async context, where the async EsClient should be used instead.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt, future::Future};
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::{
+    cat::{aliases_req, AliasResponse},
+    client::{self, EsClientBuilder, IndexPattern, Method},
+    doc::{
+        DeleteByQueryOptions,
+        DeleteDocOptions,
+        DocId,
+        EsDeleteByQueryResponse,
+        EsDeleteDocResponse,
+        EsIndexDocResponse,
+        IndexDocOptions,
+        IndexRequest,
+    },
+    errors::EsError,
+    index::{CreateIndexOptions, EsIndexCreateSuccess},
+    info::EsInfo,
+    pool::Node,
+    response::{EsResponse, Response},
+    search::{CountOptions, EsCountResponse, EsSearchResponse, SearchOptions, SearchRequest},
+    version::Version,
+};
+
+/// EsClient blocking the current thread until each request completes.
+#[derive(Debug)]
+pub struct EsClient {
+    client: client::EsClient,
+    rt: Runtime,
+}
+
+impl fmt::Display for EsClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.client.fmt(f)
+    }
+}
+
+impl Default for EsClient {
+    /// Create blocking EsClient connected to `http://localhost:9200`.
+    ///
+    /// Panics if the client can not be built, use `EsClientBuilder::build_blocking` to handle the error instead.
+    fn default() -> EsClient {
+        EsClientBuilder::new()
+            .build_blocking()
+            .expect("Failed to build EsClient!")
+    }
+}
+
+impl EsClient {
+    /// Create new blocking EsClient.
+    ///
+    /// Panics if the client can not be built, use `EsClientBuilder::build_blocking` to handle the error instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Http host for Elasticsearch.
+    /// * `port` - Port allocated for Elasticsearch connection.
+    pub fn new(host: &str, port: u16) -> EsClient {
+        EsClientBuilder::new()
+            .host(host)
+            .port(port)
+            .build_blocking()
+            .expect("Failed to build EsClient!")
+    }
+
+    /// Create new EsClientBuilder to configure the client, built with `build_blocking`.
+    pub fn builder() -> EsClientBuilder {
+        EsClientBuilder::new()
+    }
+
+    /// Wrap the async client, creating the runtime requests are sent on.
+    pub(crate) fn from_async(client: client::EsClient) -> Result<EsClient, EsError> {
+        if Handle::try_current().is_ok() {
+            return Err(EsError::Config("Can not use the blocking client from within an async context, use the async client instead.".to_owned()));
+        }
+        let rt = Builder::new_current_thread().enable_all().build()?;
+        Ok(EsClient { client, rt })
+    }
+
+    /// Helper function that runs the request on the runtime of the client.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.rt.block_on(future)
+    }
+
+    /// Async client requests are sent with, for the APIs without a blocking method.
+    pub fn get_async(&self) -> &client::EsClient {
+        &self.client
+    }
+
+    /// Version of Elasticsearch, `None` if it has not been requested yet.
+    pub fn version(&self) -> Option<&Version> {
+        self.client.version()
+    }
+
+    /// Version of Elasticsearch, requesting it from the cluster if it is not known yet.
+    pub fn detect_version(&self) -> Result<&Version, EsError> {
+        self.block_on(self.client.detect_version())
+    }
+
+    /// Nodes requests are sent to, including nodes marked dead.
+    pub fn nodes(&self) -> Vec<Node> {
+        self.client.nodes()
+    }
+
+    /// Refresh the nodes from the cluster, see `client::EsClient::sniff`.
+    pub fn sniff(&self) -> Result<Vec<Node>, EsError> {
+        self.block_on(self.client.sniff())
+    }
+
+    /// Send a request to any endpoint, see `client::EsClient::send`.
+    pub fn send(&self, method: Method, path: &str, params: &[(&str, &str)], body: Option<Value>) -> Result<EsResponse, EsError> {
+        self.block_on(self.client.send(method, path, params, body))
+    }
+
+    /// Exposed search functionality
+    pub fn search<'a, T>(&self, search_on: IndexPattern<'a>, query: Value) -> Result<Response<EsSearchResponse<T>>, EsError>
+        where for<'de> T: Deserialize<'de>
+    {
+        self.block_on(self.client.search(search_on, query))
+    }
+
+    /// Exposed search functionality with options such as timeouts
+    pub fn search_with_options<'a, T>(
+        &self,
+        search_on: IndexPattern<'a>,
+        query: Value,
+        options: SearchOptions,
+        ) -> Result<Response<EsSearchResponse<T>>, EsError>
+        where for<'de> T: Deserialize<'de>
+    {
+        self.block_on(self.client.search_with_options(search_on, query, options))
+    }
+
+    /// Start a search request, sent with `send` once its query and options are set.
+    pub fn search_request<'a, P: Into<IndexPattern<'a>>>(&'a self, search_on: P) -> SearchRequest<'a, EsClient> {
+        SearchRequest::new(self, search_on.into())
+    }
+
+    /// Exposed count functionality
+    pub fn count<'a>(&self, count_on: IndexPattern<'a>, query: Value) -> Result<Response<EsCountResponse>, EsError> {
+        self.block_on(self.client.count(count_on, query))
+    }
+
+    /// Exposed count functionality with options such as timeouts
+    pub fn count_with_options<'a>(&self, count_on: IndexPattern<'a>, query: Value, options: CountOptions) -> Result<Response<EsCountResponse>, EsError> {
+        self.block_on(self.client.count_with_options(count_on, query, options))
+    }
+
+    /// Exposed info functionality
+    pub fn info(&self) -> Result<Response<EsInfo>, EsError> {
+        self.block_on(self.client.info())
+    }
+
+    /// Exposed cat aliases functionality
+    pub fn aliases(&self) -> Result<Response<AliasResponse>, EsError> {
+        self.block_on(aliases_req(&self.client))
+    }
+
+    /// Exposed create index functionality
+    pub fn create_index(&self, index: &str) -> Result<Response<EsIndexCreateSuccess>, EsError> {
+        self.block_on(self.client.create_index(index))
+    }
+
+    /// Exposed create index functionality with options such as timeouts
+    pub fn create_index_with_options(&self, index: &str, options: CreateIndexOptions) -> Result<Response<EsIndexCreateSuccess>, EsError> {
+        self.block_on(self.client.create_index_with_options(index, options))
+    }
+
+    /// Start an index document request, sent with `send` once its document and options are set.
+    pub fn index_request<'a, P: Into<IndexPattern<'a>>>(&'a self, write_on: P) -> IndexRequest<'a, EsClient> {
+        IndexRequest::new(self, write_on.into())
+    }
+
+    /// Exposed create doc functionality
+    pub fn create_doc<'a, T: Serialize>(
+        &self,
+        write_on: IndexPattern<'a>,
+        id: DocId<'a>,
+        operation: Option<&str>,
+        data: T
+        ) -> Result<Response<EsIndexDocResponse>, EsError>
+    {
+        self.block_on(self.client.create_doc(write_on, id, operation, data))
+    }
+
+    /// Exposed create doc functionality with options such as timeouts
+    pub fn create_doc_with_options<'a, T: Serialize>(
+        &self,
+        write_on: IndexPattern<'a>,
+        id: DocId<'a>,
+        operation: Option<&str>,
+        data: T,
+        options: IndexDocOptions,
+        ) -> Result<Response<EsIndexDocResponse>, EsError>
+    {
+        self.block_on(self.client.create_doc_with_options(write_on, id, operation, data, options))
+    }
+
+    /// Exposed delete doc functionality
+    pub fn delete_doc<'a>(
+        &self,
+        delete_on: IndexPattern<'a>,
+        id: DocId<'a>,
+        ) -> Result<Response<EsDeleteDocResponse>, EsError>
+    {
+        self.block_on(self.client.delete_doc(delete_on, id))
+    }
+
+    /// Exposed delete doc functionality with options such as timeouts
+    pub fn delete_doc_with_options<'a>(
+        &self,
+        delete_on: IndexPattern<'a>,
+        id: DocId<'a>,
+        options: DeleteDocOptions,
+        ) -> Result<Response<EsDeleteDocResponse>, EsError>
+    {
+        self.block_on(self.client.delete_doc_with_options(delete_on, id, options))
+    }
+
+    /// Exposed delete by query functionality
+    pub fn delete_by_query<'a>(&self, delete_on: IndexPattern<'a>, query: Value) -> Result<Response<EsDeleteByQueryResponse>, EsError> {
+        self.block_on(self.client.delete_by_query(delete_on, query))
+    }
+
+    /// Exposed delete by query functionality with options such as timeouts
+    pub fn delete_by_query_with_options<'a>(
+        &self,
+        delete_on: IndexPattern<'a>,
+        query: Value,
+        options: DeleteByQueryOptions,
+        ) -> Result<Response<EsDeleteByQueryResponse>, EsError>
+    {
+        self.block_on(self.client.delete_by_query_with_options(delete_on, query, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::{EsClientBuilder, IndexPattern, Version},
+        doc::{DocId, IndexDocOptions},
+        errors::EsError,
+        request::Refresh,
+        search::SearchOptions,
+        transport::{InMemoryTransport, TransportResponse},
+    };
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn send_blocking_requests() {
+        let transport = Arc::new(InMemoryTransport::new(|request| match request.method().as_str() {
+            "GET" => Ok(TransportResponse::new(StatusCode::OK, r#"{
+                "name": "es-1",
+                "cluster_name": "docker-cluster",
+                "cluster_uuid": "HjwlCaVKQo2766zcX_l7DQ",
+                "version": {"number": "7.10.0", "build_flavor": "default", "build_type": "docker", "build_hash": "51e9d6f", "build_date": "2020-11-09T21:30:33.964949Z", "build_snapshot": false, "lucene_version": "8.7.0", "minimum_wire_compatibility_version": "6.8.0", "minimum_index_compatibility_version": "6.0.0-beta1"},
+                "tagline": "You Know, for Search"
+            }"#)),
+            "PUT" => Ok(TransportResponse::new(StatusCode::CREATED, r#"{
+                "_index": "test", "_type": "_doc", "_id": "1", "_version": 1, "result": "created",
+                "_shards": {"total": 2, "successful": 1, "failed": 0}, "_seq_no": 0, "_primary_term": 1
+            }"#)),
+            _ => Ok(TransportResponse::new(StatusCode::OK, r#"{"count": 1, "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0}}"#)),
+        }));
+        let client = EsClientBuilder::new()
+            .transport(transport.clone())
+            .build_blocking()
+            .unwrap();
+
+        assert_eq!(client.version().map(|version| version.to_string()), Some("Elasticsearch 7.10.0".to_owned()));
+        let res = client.create_doc(IndexPattern::index("test"), DocId::assigned("1"), None, json!({"a": "test"})).unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(client.count(IndexPattern::index("test"), json!({})).unwrap().count, 1);
+
+        let urls: Vec<String> = transport.requests().iter().map(|request| request.url().to_owned()).collect();
+        assert_eq!(urls, vec![
+            "http://localhost:9200/",
+            "http://localhost:9200/test/_doc/1",
+            "http://localhost:9200/test/_count",
+        ]);
+    }
+
+    #[test]
+    fn send_blocking_request_builders() {
+        let transport = Arc::new(InMemoryTransport::new(|request| match request.method().as_str() {
+            "PUT" => Ok(TransportResponse::new(StatusCode::CREATED, r#"{
+                "_index": "test", "_type": "_doc", "_id": "1", "_version": 1, "result": "created",
+                "_shards": {"total": 2, "successful": 1, "failed": 0}, "_seq_no": 0, "_primary_term": 1
+            }"#)),
+            _ => Ok(TransportResponse::new(StatusCode::OK, r#"{
+                "took": 1,
+                "timed_out": false,
+                "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0},
+                "hits": {"total": {"value": 0, "relation": "eq"}, "max_score": null, "hits": []}
+            }"#)),
+        }));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build_blocking()
+            .unwrap();
+
+        let res = client.index_request("test")
+            .id("1")
+            .options(IndexDocOptions::new().refresh(Refresh::True))
+            .document(&json!({"a": "test"}))
+            .send()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let res = client.search_request("test")
+            .query(json!({"match_all": {}}))
+            .options(SearchOptions::new().routing("u1"))
+            .send::<Value>()
+            .unwrap();
+        assert_eq!(res.total(), 0);
+
+        let urls: Vec<String> = transport.requests().iter().map(|request| request.url().to_owned()).collect();
+        assert_eq!(urls, vec![
+            "http://localhost:9200/test/_doc/1?refresh=true",
+            "http://localhost:9200/test/_search?routing=u1",
+        ]);
+    }
+
+    #[test]
+    fn build_blocking_in_async_context() {
+        let rt = Runtime::new().unwrap();
+        let client = rt.block_on(async {
+            EsClientBuilder::new().version(Version::new(7, 10, 0)).build_blocking()
+        });
+        assert!(matches!(client, Err(EsError::Config(_))));
+    }
+}
@@ -1,6 +1,8 @@
-use reqwest;
 use reqwest::StatusCode;
 use crate::client::EsClient;
+use crate::errors::EsError;
+use crate::response::Response;
+use crate::trace::op_span;
 
 #[derive(Debug, PartialEq)]
 pub struct AliasResponse {
@@ -16,41 +18,41 @@ pub struct AliasResults {
     routing_search: String,
 }
 
-pub async fn aliases_req(client: &EsClient) -> Result<AliasResponse, Box<dyn std::error::Error>> {
-    let res = client.get(Some("_cat/aliases"))
-        .send()
-        .await?;
-
-    let res = match res.status() {
-        StatusCode::OK => {
-            let text = res.text().await?;
-            // Elasticsearch does not send back "json" formatted response, so the text ends up
-            // being a str or all field values (if aliases) or empty string.
-            if text.len() == 0 {
-               AliasResponse { aliases: Vec::new() }
-            } else {
-                let aliases_text: Vec<&str> = text.split("\n").collect();
+pub async fn aliases_req(client: &EsClient) -> Result<Response<AliasResponse>, EsError> {
+    op_span!("cat.aliases").run(client, async move {
+        client.detect_version().await?;
+
+        let res = client.send_request(client.get_request(Some("_cat/aliases"))).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => {
+                // Elasticsearch does not send back "json" formatted response, so the text ends up
+                // being a str or all field values (if aliases) or empty string.
                 let mut aliases = Vec::new();
-                for alias in aliases_text {
-                    if alias.len() > 0 {
-                        let alias_vec: Vec<&str> = alias.split(" ").collect();
-                        aliases.push(
+                for alias in text.lines().filter(|alias| !alias.trim().is_empty()) {
+                    let alias_vec: Vec<&str> = alias.split_whitespace().collect();
+                    match alias_vec.as_slice() {
+                        [alias, index, filter, routing_index, routing_search, ..] => aliases.push(
                             AliasResults {
-                                alias: alias_vec[0].to_owned(),
-                                index: alias_vec[1].to_owned(),
-                                filter: alias_vec[2].to_owned(),
-                                routing_index: alias_vec[3].to_owned(),
-                                routing_search: alias_vec[4].to_owned(),
-                            });
+                                alias: (*alias).to_owned(),
+                                index: (*index).to_owned(),
+                                filter: (*filter).to_owned(),
+                                routing_index: (*routing_index).to_owned(),
+                                routing_search: (*routing_search).to_owned(),
+                            }),
+                        _ => return Err(EsError::Deserialize(serde::de::Error::custom(
+                            format!("unexpected alias line: {}", alias)
+                        ))),
                     }
                 }
-                AliasResponse { aliases }
-            }
-        },
-        _ => panic!("Request failed in an unexpected way..."),
-    };
-
-    Ok(res)
+                Ok(Response::new(AliasResponse { aliases }, res))
+            },
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 #[cfg(test)]
@@ -102,7 +104,7 @@ mod tests {
         let expected_res = AliasResponse {
             aliases: Vec::new()
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -151,6 +153,6 @@ mod tests {
                 }
             ]
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 }
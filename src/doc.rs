@@ -1,17 +1,20 @@
-use reqwest;
 use reqwest::StatusCode;
+use std::{borrow::Cow, fmt, time::Duration};
 
 use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use crate::blocking;
 use crate::{
     client::{
         EsClient,
         IndexPattern,
     },
+    request::{common_options, ActiveShards, CommonOptions, EsRequest, Refresh, WriteOptions},
+    response::Response,
     utils::serialize_response,
-    errors::{
-        ESGenericFail,
-        ESMissingId,
-    }
+    errors::EsError,
+    trace::{self, op_span},
 };
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -21,7 +24,7 @@ pub struct EsIndexDocResponse {
     #[serde(rename = "_index")]
     index: String,
     #[serde(rename = "_type")]
-    doc_type: String,
+    doc_type: Option<String>,
     #[serde(rename = "_id")]
     id: String,
     #[serde(rename = "_version")]
@@ -40,7 +43,7 @@ pub struct EsDeleteDocResponse {
     #[serde(rename = "_index")]
     index: String,
     #[serde(rename = "_type")]
-    doc_type: String,
+    doc_type: Option<String>,
     #[serde(rename = "_id")]
     id: String,
     #[serde(rename = "_version")]
@@ -52,6 +55,18 @@ pub struct EsDeleteDocResponse {
     result: String,
 }
 
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct EsDeleteByQueryResponse {
+    pub took: u64,
+    pub timed_out: bool,
+    pub total: u64,
+    pub deleted: u64,
+    pub batches: u64,
+    pub version_conflicts: u64,
+    pub noops: u64,
+    pub failures: Vec<Value>,
+}
+
 #[derive(Deserialize, Debug, PartialEq)]
 struct ShardResults {
     total: u16,
@@ -60,9 +75,355 @@ struct ShardResults {
 }
 
 /// Assign an id at doc creation time or let system decide.
+#[derive(Debug, Clone, PartialEq)]
 pub enum DocId<'a> {
     Unassigned,
-    Assigned(&'a str),
+    Assigned(Cow<'a, str>),
+}
+
+impl<'a> DocId<'a> {
+    /// Id assigned to the document, borrowed or owned.
+    pub fn assigned<S: Into<Cow<'a, str>>>(id: S) -> DocId<'a> {
+        DocId::Assigned(id.into())
+    }
+
+    /// Copy the borrowed id, so the id no longer borrows it.
+    pub fn into_owned(self) -> DocId<'static> {
+        match self {
+            DocId::Unassigned => DocId::Unassigned,
+            DocId::Assigned(id) => DocId::Assigned(Cow::Owned(id.into_owned())),
+        }
+    }
+}
+
+/// Options of the index document request.
+#[derive(Debug, Clone, Default)]
+pub struct IndexDocOptions {
+    refresh: Option<Refresh>,
+    routing: Option<String>,
+    pipeline: Option<String>,
+    if_seq_no: Option<u64>,
+    if_primary_term: Option<u64>,
+    version: Option<u64>,
+    version_type: Option<VersionType>,
+    require_alias: Option<bool>,
+    write: WriteOptions,
+    common: CommonOptions,
+}
+
+common_options!(IndexDocOptions);
+
+impl IndexDocOptions {
+    /// Create new IndexDocOptions.
+    pub fn new() -> IndexDocOptions {
+        IndexDocOptions::default()
+    }
+
+    /// Set when the change is made visible to search, sent as `refresh`.
+    pub fn refresh(mut self, refresh: Refresh) -> IndexDocOptions {
+        self.refresh = Some(refresh);
+        self
+    }
+
+    /// Set the value used to route the request to a shard, sent as `routing`.
+    pub fn routing(mut self, routing: &str) -> IndexDocOptions {
+        self.routing = Some(routing.to_owned());
+        self
+    }
+
+    /// Set the ingest pipeline the document is sent through, sent as `pipeline`.
+    pub fn pipeline(mut self, pipeline: &str) -> IndexDocOptions {
+        self.pipeline = Some(pipeline.to_owned());
+        self
+    }
+
+    /// Set how long the cluster waits for unavailable shards, sent as `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> IndexDocOptions {
+        self.write.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the shard copies that must be active before the request proceeds, sent as
+    /// `wait_for_active_shards`.
+    pub fn wait_for_active_shards(mut self, wait_for_active_shards: ActiveShards) -> IndexDocOptions {
+        self.write.wait_for_active_shards = Some(wait_for_active_shards);
+        self
+    }
+
+    /// Only write the document if its last change has this sequence number, sent as `if_seq_no`.
+    pub fn if_seq_no(mut self, if_seq_no: u64) -> IndexDocOptions {
+        self.if_seq_no = Some(if_seq_no);
+        self
+    }
+
+    /// Only write the document if its last change has this primary term, sent as
+    /// `if_primary_term`.
+    pub fn if_primary_term(mut self, if_primary_term: u64) -> IndexDocOptions {
+        self.if_primary_term = Some(if_primary_term);
+        self
+    }
+
+    /// Set the version of the document, checked as set with `version_type`, sent as `version`.
+    pub fn version(mut self, version: u64) -> IndexDocOptions {
+        self.version = Some(version);
+        self
+    }
+
+    /// Set how `version` is compared with the version of the document, sent as `version_type`.
+    pub fn version_type(mut self, version_type: VersionType) -> IndexDocOptions {
+        self.version_type = Some(version_type);
+        self
+    }
+
+    /// Set whether the target must be an alias, sent as `require_alias`.
+    pub fn require_alias(mut self, require_alias: bool) -> IndexDocOptions {
+        self.require_alias = Some(require_alias);
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(refresh) = self.refresh {
+            request = request.param("refresh", refresh);
+        }
+        if let Some(routing) = self.routing {
+            request = request.param("routing", routing);
+        }
+        if let Some(pipeline) = self.pipeline {
+            request = request.param("pipeline", pipeline);
+        }
+        if let Some(if_seq_no) = self.if_seq_no {
+            request = request.param("if_seq_no", if_seq_no);
+        }
+        if let Some(if_primary_term) = self.if_primary_term {
+            request = request.param("if_primary_term", if_primary_term);
+        }
+        if let Some(version) = self.version {
+            request = request.param("version", version);
+        }
+        if let Some(version_type) = self.version_type {
+            request = request.param("version_type", version_type);
+        }
+        if let Some(require_alias) = self.require_alias {
+            request = request.param("require_alias", require_alias);
+        }
+        self.common.apply(self.write.apply(request))
+    }
+}
+
+/// Options of the delete document request.
+#[derive(Debug, Clone, Default)]
+pub struct DeleteDocOptions {
+    refresh: Option<Refresh>,
+    routing: Option<String>,
+    write: WriteOptions,
+    common: CommonOptions,
+}
+
+common_options!(DeleteDocOptions);
+
+impl DeleteDocOptions {
+    /// Create new DeleteDocOptions.
+    pub fn new() -> DeleteDocOptions {
+        DeleteDocOptions::default()
+    }
+
+    /// Set when the change is made visible to search, sent as `refresh`.
+    pub fn refresh(mut self, refresh: Refresh) -> DeleteDocOptions {
+        self.refresh = Some(refresh);
+        self
+    }
+
+    /// Set the value used to route the request to a shard, sent as `routing`.
+    pub fn routing(mut self, routing: &str) -> DeleteDocOptions {
+        self.routing = Some(routing.to_owned());
+        self
+    }
+
+    /// Set how long the cluster waits for unavailable shards, sent as `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> DeleteDocOptions {
+        self.write.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the shard copies that must be active before the request proceeds, sent as
+    /// `wait_for_active_shards`.
+    pub fn wait_for_active_shards(mut self, wait_for_active_shards: ActiveShards) -> DeleteDocOptions {
+        self.write.wait_for_active_shards = Some(wait_for_active_shards);
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(refresh) = self.refresh {
+            request = request.param("refresh", refresh);
+        }
+        if let Some(routing) = self.routing {
+            request = request.param("routing", routing);
+        }
+        self.common.apply(self.write.apply(request))
+    }
+}
+
+/// Options of the delete by query request.
+#[derive(Debug, Clone, Default)]
+pub struct DeleteByQueryOptions {
+    refresh: Option<bool>,
+    routing: Option<String>,
+    write: WriteOptions,
+    common: CommonOptions,
+}
+
+common_options!(DeleteByQueryOptions);
+
+impl DeleteByQueryOptions {
+    /// Create new DeleteByQueryOptions.
+    pub fn new() -> DeleteByQueryOptions {
+        DeleteByQueryOptions::default()
+    }
+
+    /// Set whether the affected shards are refreshed once the request completes, sent as
+    /// `refresh`.
+    pub fn refresh(mut self, refresh: bool) -> DeleteByQueryOptions {
+        self.refresh = Some(refresh);
+        self
+    }
+
+    /// Set the value used to route the request to a shard, sent as `routing`.
+    pub fn routing(mut self, routing: &str) -> DeleteByQueryOptions {
+        self.routing = Some(routing.to_owned());
+        self
+    }
+
+    /// Set how long each batch of deletes waits for unavailable shards, sent as `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> DeleteByQueryOptions {
+        self.write.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the shard copies that must be active before each batch of deletes proceeds, sent as
+    /// `wait_for_active_shards`.
+    pub fn wait_for_active_shards(mut self, wait_for_active_shards: ActiveShards) -> DeleteByQueryOptions {
+        self.write.wait_for_active_shards = Some(wait_for_active_shards);
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(refresh) = self.refresh {
+            request = request.param("refresh", refresh);
+        }
+        if let Some(routing) = self.routing {
+            request = request.param("routing", routing);
+        }
+        self.common.apply(self.write.apply(request))
+    }
+}
+
+/// Operation of the index document request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpType {
+    /// Create the document or replace it if it exists.
+    Index,
+    /// Create the document, failing if it already exists.
+    Create,
+}
+
+/// How the version of the request is compared with the version of the document, sent as
+/// `version_type`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionType {
+    /// The version must match the version of the document.
+    Internal,
+    /// The version must be greater than the version of the document.
+    External,
+    /// The version must be greater than or equal to the version of the document.
+    ExternalGte,
+}
+
+impl fmt::Display for VersionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VersionType::Internal => write!(f, "internal"),
+            VersionType::External => write!(f, "external"),
+            VersionType::ExternalGte => write!(f, "external_gte"),
+        }
+    }
+}
+
+/// Index document request built step by step, created with `EsClient::index_request`.
+#[derive(Debug)]
+pub struct IndexRequest<'a, C = EsClient> {
+    client: &'a C,
+    write_on: IndexPattern<'a>,
+    id: DocId<'a>,
+    op_type: OpType,
+    document: Option<Result<Value, EsError>>,
+    options: IndexDocOptions,
+}
+
+impl<'a, C> IndexRequest<'a, C> {
+    pub(crate) fn new(client: &'a C, write_on: IndexPattern<'a>) -> IndexRequest<'a, C> {
+        IndexRequest {
+            client,
+            write_on,
+            id: DocId::Unassigned,
+            op_type: OpType::Index,
+            document: None,
+            options: IndexDocOptions::default(),
+        }
+    }
+
+    /// Set the id of the document, assigned by Elasticsearch if not set.
+    pub fn id<S: Into<Cow<'a, str>>>(mut self, id: S) -> IndexRequest<'a, C> {
+        self.id = DocId::assigned(id);
+        self
+    }
+
+    /// Set whether an existing document is replaced, `OpType::Create` requires an id.
+    pub fn op_type(mut self, op_type: OpType) -> IndexRequest<'a, C> {
+        self.op_type = op_type;
+        self
+    }
+
+    /// Set the document, serialized as the body of the request.
+    pub fn document<T: Serialize>(mut self, document: &T) -> IndexRequest<'a, C> {
+        self.document = Some(serde_json::to_value(document).map_err(EsError::from));
+        self
+    }
+
+    /// Set the options of the request, such as `refresh`, `routing` and `if_seq_no`.
+    pub fn options(mut self, options: IndexDocOptions) -> IndexRequest<'a, C> {
+        self.options = options;
+        self
+    }
+
+    /// Send the request with the async client, failing if the document is not set.
+    async fn send_with(self, client: &EsClient) -> Result<Response<EsIndexDocResponse>, EsError> {
+        let document = self.document
+            .ok_or_else(|| EsError::Config("Document of the index request is not set, use document first.".to_owned()))??;
+        let operation = match (self.op_type, &self.id) {
+            (OpType::Index, _) => None,
+            (OpType::Create, DocId::Assigned(_)) => Some("_create"),
+            (OpType::Create, DocId::Unassigned) => return Err(EsError::MissingId),
+        };
+
+        index_doc_req(client, self.write_on, self.id, operation, document, self.options).await
+    }
+}
+
+impl<'a> IndexRequest<'a> {
+    /// Send the request, failing if the document is not set.
+    pub async fn send(self) -> Result<Response<EsIndexDocResponse>, EsError> {
+        let client = self.client;
+        self.send_with(client).await
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<'a> IndexRequest<'a, blocking::EsClient> {
+    /// Send the request, blocking until it completes, failing if the document is not set.
+    pub fn send(self) -> Result<Response<EsIndexDocResponse>, EsError> {
+        let client = self.client;
+        client.block_on(self.send_with(client.get_async()))
+    }
 }
 
 pub async fn index_doc_req<'a, T: Serialize>(
@@ -70,80 +431,90 @@ pub async fn index_doc_req<'a, T: Serialize>(
     write_on: IndexPattern<'a>,
     id: DocId<'a>,
     operation: Option<&str>,
-    data: T
-    ) -> Result<EsIndexDocResponse, Box<dyn std::error::Error>> 
+    data: T,
+    options: IndexDocOptions,
+    ) -> Result<Response<EsIndexDocResponse>, EsError>
 {
-    let (index, doc_type) = match write_on {
-        IndexPattern::Index(index) => (index, None),
-        IndexPattern::IndexType(index, doc_type) => (index, Some(doc_type))
-    };
+    op_span!("index").run(client, async move {
+        client.detect_version().await?;
 
-    // Check if id is passed to use either PUT method or POST.
-    let res = match id {
-        DocId::Assigned(id) => client.put_doc(index, doc_type, id, operation)
-            .json(&data)
-            .send()
-            .await?,
-        DocId::Unassigned => client.post_doc(index, doc_type)
-            .json(&data)
-            .send()
-            .await?,
-    };
+        let (index, doc_type) = write_on.single_target()?;
+        trace::record_index(index);
 
-    let res = match res.status() {
-        StatusCode::OK => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsIndexDocResponse>(&text)?;
-            data
-        },
-        StatusCode::CREATED => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsIndexDocResponse>(&text)?;
-            data
-        },
-        StatusCode::BAD_REQUEST => {
-            let text = res.text().await?;
-            let data = serialize_response::<ESGenericFail>(&text)?;
-            return Err(Box::new(data));
-        },
-        _ => panic!("Request failed in an unexpected way..."),
-    };
-    Ok(res)
+        // Check if id is passed to use either PUT method or POST.
+        let request = match id {
+            DocId::Assigned(id) => client.put_doc_request(index, doc_type, &id, operation)?,
+            DocId::Unassigned => client.post_doc_request(index, doc_type)?,
+        };
+        let res = client.send_request(options.apply(request.json(&data)?)).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK | StatusCode::CREATED => Ok(Response::new(serialize_response::<EsIndexDocResponse>(&text)?, res)),
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 pub async fn delete_doc_req<'a>(
     client: &EsClient,
     delete_on: IndexPattern<'a>,
-    id: DocId<'a>
-) -> Result<EsDeleteDocResponse, Box<dyn std::error::Error>>
+    id: DocId<'a>,
+    options: DeleteDocOptions,
+) -> Result<Response<EsDeleteDocResponse>, EsError>
 {
-    let (index, doc_type) = match delete_on {
-        IndexPattern::Index(index) => (index, None),
-        IndexPattern::IndexType(index, doc_type) => (index, Some(doc_type))
-    };
+    op_span!("delete").run(client, async move {
+        client.detect_version().await?;
 
-    let res = match id {
-        DocId::Assigned(id) => client.delete_doc_by_id(index, doc_type, id)
-            .send()
-            .await?,
-        DocId::Unassigned => return Err(Box::new(ESMissingId::new()))
-    };
+        let (index, doc_type) = delete_on.single_target()?;
+        trace::record_index(index);
 
-    let res = match res.status() {
-        StatusCode::OK => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsDeleteDocResponse>(&text)?;
-            data
-        },
-        StatusCode::NOT_FOUND => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsDeleteDocResponse>(&text)?;
-            data
-        },
-        _ => panic!("Request failed in an unexpected way..."),
-    };
+        let request = match id {
+            DocId::Assigned(id) => client.delete_doc_request(index, doc_type, &id)?,
+            DocId::Unassigned => return Err(EsError::MissingId)
+        };
+        let res = client.send_request(options.apply(request)).await?;
 
-    Ok(res)
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            // A missing document is still reported with the delete response.
+            StatusCode::OK | StatusCode::NOT_FOUND => match serialize_response::<EsDeleteDocResponse>(&text) {
+                Ok(data) => Ok(Response::new(data, res)),
+                Err(_) => Err(EsError::from_response(status, text)),
+            },
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
+}
+
+pub async fn delete_by_query_req<'a>(client: &EsClient, delete_on: IndexPattern<'a>, query: Value, options: DeleteByQueryOptions) -> Result<Response<EsDeleteByQueryResponse>, EsError> {
+    op_span!("delete_by_query").run(client, async move {
+        client.detect_version().await?;
+
+        let (index, doc_type) = delete_on.multi_target()?;
+        trace::record_index(&index);
+
+        client.check_doc_type(doc_type)?;
+
+        let request = client.post_request(&index, doc_type, Some("_delete_by_query")).json(&query)?;
+        let res = client.send_request(options.apply(request)).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => {
+                let body = serialize_response::<EsDeleteByQueryResponse>(&text)?;
+                trace::record_took(body.took);
+                Ok(Response::new(body, res))
+            },
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 #[cfg(test)]
@@ -151,21 +522,36 @@ mod tests {
     use super::{
         index_doc_req,
         delete_doc_req,
+        delete_by_query_req,
         EsIndexDocResponse,
         EsDeleteDocResponse,
         ShardResults,
         DocId,
+        DeleteByQueryOptions,
+        DeleteDocOptions,
+        IndexDocOptions,
+        OpType,
+        VersionType,
     };
     use crate::{
         client::{
             EsClient,
+            EsClientBuilder,
             IndexPattern,
+            Method,
+            Version,
         },
+        errors::EsError,
+        request::{ActiveShards, Refresh},
+        transport::{InMemoryTransport, TransportResponse},
     };
 
-    use mockito::mock;
+    use mockito::{mock, Matcher};
+    use reqwest::StatusCode;
+    use std::{sync::Arc, time::Duration};
     use tokio::runtime::Runtime;
     use serde::Serialize;
+    use serde_json::json;
 
     #[derive(Serialize, Debug, PartialEq)]
     struct Data {
@@ -224,16 +610,17 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::Index("test"),
-            DocId::Assigned("1"),
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsIndexDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "1".to_owned(),
             version: 1,
             result: "created".to_owned(),
@@ -245,7 +632,7 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -299,16 +686,17 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             DocId::Unassigned,
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsIndexDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "abcdefg".to_owned(),
             version: 1,
             result: "created".to_owned(),
@@ -320,7 +708,7 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -374,16 +762,17 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::Index("test"),
-            DocId::Assigned("1"),
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsIndexDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "1".to_owned(),
             version: 1,
             result: "updated".to_owned(),
@@ -395,7 +784,7 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -449,16 +838,17 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             DocId::Unassigned,
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsIndexDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "abcdefg".to_owned(),
             version: 1,
             result: "updated".to_owned(),
@@ -470,7 +860,7 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -521,18 +911,18 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::IndexType("test", "doc"),
-            DocId::Assigned("1"),
+            IndexPattern::index_type("test", "doc"),
+            DocId::assigned("1"),
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
         let res = rt.block_on(res);
-        assert_eq!(res.is_err(), true);
+        assert!(res.is_err());
     }
 
     #[test]
-    #[should_panic]
     fn unexpected_error_es6() {
         let rt = Runtime::new().unwrap();
         let _client_mock = mock("GET", "/")
@@ -557,7 +947,7 @@ mod tests {
             }"#)
             .create();
 
-        let _create_doc_mock = mock("PUT", "/test/_doc/1")
+        let create_doc_mock = mock("PUT", "/test-error/doc/1")
             .with_status(501)
             .with_header("content-type", "application/json")
             .with_body(r#"{
@@ -571,6 +961,7 @@ mod tests {
                 },
                 "status": 501 
             }"#)
+            .expect(1)
             .create();
 
         let client = EsClient::new("http://127.0.0.1", 1234);
@@ -580,13 +971,21 @@ mod tests {
         };
         let res = index_doc_req::<Data>(
             &client,
-            IndexPattern::IndexType("test", "doc"),
-            DocId::Assigned("1"),
+            IndexPattern::index_type("test-error", "doc"),
+            DocId::assigned("1"),
             None,
             doc,
+            IndexDocOptions::default(),
         );
 
-        let _res = rt.block_on(res);
+        match rt.block_on(res) {
+            Err(EsError::Api { status, error_type, .. }) => {
+                assert_eq!(status, 501);
+                assert_eq!(error_type, "corrupt request query");
+            },
+            res => panic!("Unexpected response: {:?}", res),
+        }
+        create_doc_mock.assert();
     }
 
     #[test]
@@ -636,14 +1035,15 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = delete_doc_req(
             &client,
-            IndexPattern::Index("test"),
-            DocId::Assigned("1"),
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
+            DeleteDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsDeleteDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "1".to_owned(),
             version: 1,
             result: "deleted".to_owned(),
@@ -655,7 +1055,7 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -686,12 +1086,13 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = delete_doc_req(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             DocId::Unassigned,
+            DeleteDocOptions::default(),
         );
 
         let res = rt.block_on(res);
-        assert_eq!(res.is_err(), true);
+        assert!(res.is_err());
     }
 
     #[test]
@@ -741,14 +1142,15 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = delete_doc_req(
             &client,
-            IndexPattern::Index("test"),
-            DocId::Assigned("19393"),
+            IndexPattern::index("test"),
+            DocId::assigned("19393"),
+            DeleteDocOptions::default(),
         );
 
         let res = rt.block_on(res);
         let expected_res = EsDeleteDocResponse {
             index: "test".to_owned(),
-            doc_type: "_doc".to_owned(),
+            doc_type: Some("_doc".to_owned()),
             id: "19393".to_owned(),
             version: 1,
             result: "not_found".to_owned(),
@@ -760,6 +1162,390 @@ mod tests {
             seq_no: 0,
             primary_term: 1,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
+    }
+
+    #[test]
+    fn successful_create_doc_with_operation_es7() {
+        let rt = Runtime::new().unwrap();
+        let _create_doc_mock = mock("PUT", "/test/_create/1")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "_index": "test",
+                "_type": "_doc",
+                "_id": "1",
+                "_version": 1,
+                "result": "created",
+                "_shards": {
+                    "total": 2,
+                    "successful": 1,
+                    "failed": 0
+                },
+                "_seq_no": 0,
+                "_primary_term": 1
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let doc = Data {
+            a: "test".to_owned(),
+            b: 5,
+        };
+        let res = index_doc_req::<Data>(
+            &client,
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
+            Some("_create"),
+            doc,
+            IndexDocOptions::default(),
+        );
+
+        let res = rt.block_on(res);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn successful_update_doc_with_operation_es6() {
+        let rt = Runtime::new().unwrap();
+        let _update_doc_mock = mock("POST", "/test/_doc/1/_update")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "_index": "test",
+                "_type": "_doc",
+                "_id": "1",
+                "_version": 2,
+                "result": "updated",
+                "_shards": {
+                    "total": 2,
+                    "successful": 1,
+                    "failed": 0
+                },
+                "_seq_no": 1,
+                "_primary_term": 1
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(6, 8, 6))
+            .build()
+            .unwrap();
+        let doc = Data {
+            a: "test".to_owned(),
+            b: 5,
+        };
+        let res = index_doc_req::<Data>(
+            &client,
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
+            Some("_update"),
+            doc,
+            IndexDocOptions::default(),
+        );
+
+        let res = rt.block_on(res);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn successful_create_doc_without_type_es8() {
+        let rt = Runtime::new().unwrap();
+        let _create_doc_mock = mock("PUT", "/test/_doc/1")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "_index": "test",
+                "_id": "1",
+                "_version": 1,
+                "result": "created",
+                "_shards": {
+                    "total": 2,
+                    "successful": 1,
+                    "failed": 0
+                },
+                "_seq_no": 0,
+                "_primary_term": 1
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(8, 11, 3))
+            .build()
+            .unwrap();
+        let doc = Data {
+            a: "test".to_owned(),
+            b: 5,
+        };
+        let res = index_doc_req::<Data>(
+            &client,
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
+            None,
+            doc,
+            IndexDocOptions::default(),
+        );
+
+        let res = rt.block_on(res);
+        assert_eq!(res.unwrap().doc_type, None);
+    }
+
+    #[test]
+    fn fail_create_doc_with_type_es8() {
+        let rt = Runtime::new().unwrap();
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(8, 11, 3))
+            .build()
+            .unwrap();
+        let doc = Data {
+            a: "test".to_owned(),
+            b: 5,
+        };
+        let res = index_doc_req::<Data>(
+            &client,
+            IndexPattern::index_type("test", "doc"),
+            DocId::assigned("1"),
+            None,
+            doc,
+            IndexDocOptions::default(),
+        );
+
+        match rt.block_on(res) {
+            Err(EsError::InvalidTarget(_)) => (),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn fail_delete_doc_without_type_es5() {
+        let rt = Runtime::new().unwrap();
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(5, 6, 16))
+            .build()
+            .unwrap();
+        let res = delete_doc_req(
+            &client,
+            IndexPattern::index("test"),
+            DocId::assigned("1"),
+            DeleteDocOptions::default(),
+        );
+
+        match rt.block_on(res) {
+            Err(EsError::InvalidTarget(_)) => (),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn successful_index_doc_with_options_es7() {
+        let rt = Runtime::new().unwrap();
+        let index_doc_mock = mock("PUT", "/params/_doc/1")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("refresh".into(), "wait_for".into()),
+                Matcher::UrlEncoded("routing".into(), "u1".into()),
+                Matcher::UrlEncoded("pipeline".into(), "geoip".into()),
+                Matcher::UrlEncoded("wait_for_active_shards".into(), "all".into()),
+                Matcher::UrlEncoded("version_type".into(), "external".into()),
+            ]))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "_index": "params",
+                "_type": "_doc",
+                "_id": "1",
+                "_version": 1,
+                "result": "created",
+                "_shards": {
+                    "total": 2,
+                    "successful": 1,
+                    "failed": 0
+                },
+                "_seq_no": 0,
+                "_primary_term": 1
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let doc = Data {
+            a: "test".to_owned(),
+            b: 5,
+        };
+        let options = IndexDocOptions::new()
+            .refresh(Refresh::WaitFor)
+            .routing("u1")
+            .pipeline("geoip")
+            .wait_for_active_shards(ActiveShards::All)
+            .param("version_type", "external");
+        let res = index_doc_req::<Data>(
+            &client,
+            IndexPattern::index("params"),
+            DocId::assigned("1"),
+            None,
+            doc,
+            options,
+        );
+
+        rt.block_on(res).unwrap();
+        index_doc_mock.assert();
+    }
+
+    #[test]
+    fn successful_delete_by_query_all_es7() {
+        let rt = Runtime::new().unwrap();
+        let delete_mock = mock("POST", "/_all/_delete_by_query")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("refresh".into(), "true".into()),
+                Matcher::UrlEncoded("timeout".into(), "120s".into()),
+                Matcher::UrlEncoded("conflicts".into(), "proceed".into()),
+            ]))
+            .match_body(r#"{"query":{"term":{"a":"test"}}}"#)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "took": 147,
+                "timed_out": false,
+                "total": 2,
+                "deleted": 2,
+                "batches": 1,
+                "version_conflicts": 0,
+                "noops": 0,
+                "retries": { "bulk": 0, "search": 0 },
+                "throttled_millis": 0,
+                "requests_per_second": -1.0,
+                "throttled_until_millis": 0,
+                "failures": []
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let options = DeleteByQueryOptions::new()
+            .refresh(true)
+            .timeout(Duration::from_secs(120))
+            .param("conflicts", "proceed");
+        let res = delete_by_query_req(&client, IndexPattern::All, json!({ "query": { "term": { "a": "test" } } }), options);
+
+        assert_eq!(rt.block_on(res).unwrap().deleted, 2);
+        delete_mock.assert();
+    }
+
+    #[test]
+    fn fail_index_doc_with_multi_target() {
+        let rt = Runtime::new().unwrap();
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let res = index_doc_req(
+            &client,
+            IndexPattern::All,
+            DocId::assigned("1"),
+            None,
+            json!({ "a": "test" }),
+            IndexDocOptions::default(),
+        );
+
+        assert!(matches!(rt.block_on(res), Err(EsError::InvalidTarget(_))));
+    }
+
+    #[test]
+    fn index_doc_with_builder() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::CREATED, r#"{
+            "_shards": {"total": 2, "failed": 0, "successful": 1},
+            "_index": "test",
+            "_id": "1",
+            "_version": 1,
+            "_seq_no": 0,
+            "_primary_term": 1,
+            "result": "created"
+        }"#))));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let doc = Data { a: "test".to_owned(), b: 5 };
+
+        let res = client.index_request("test")
+            .id("1")
+            .op_type(OpType::Create)
+            .options(IndexDocOptions::new().refresh(Refresh::WaitFor).routing("u1"))
+            .document(&doc)
+            .send();
+        assert_eq!(rt.block_on(res).unwrap().status(), StatusCode::CREATED);
+
+        let requests = transport.requests();
+        assert_eq!(*requests[0].method(), Method::PUT);
+        assert_eq!(requests[0].url(), "http://localhost:9200/test/_create/1?refresh=wait_for&routing=u1");
+        assert_eq!(requests[0].body(), Some(br#"{"a":"test","b":5}"#.as_ref()));
+
+        let res = client.index_request("test").op_type(OpType::Create).document(&doc).send();
+        assert!(matches!(rt.block_on(res), Err(EsError::MissingId)));
+        assert!(matches!(rt.block_on(client.index_request("test").send()), Err(EsError::Config(_))));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn index_doc_with_builder_versions() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, r#"{
+            "_shards": {"total": 2, "failed": 0, "successful": 1},
+            "_index": "test",
+            "_id": "1",
+            "_version": 8,
+            "_seq_no": 4,
+            "_primary_term": 1,
+            "result": "updated"
+        }"#))));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let doc = Data { a: "test".to_owned(), b: 5 };
+
+        let res = client.index_request("test")
+            .id("1")
+            .options(IndexDocOptions::new().if_seq_no(3).if_primary_term(1).require_alias(false))
+            .document(&doc)
+            .send();
+        rt.block_on(res).unwrap();
+        let res = client.index_request("test")
+            .id("1")
+            .options(IndexDocOptions::new().version(8).version_type(VersionType::ExternalGte))
+            .document(&doc)
+            .send();
+        rt.block_on(res).unwrap();
+
+        let urls: Vec<String> = transport.requests().iter().map(|request| request.url().to_owned()).collect();
+        assert_eq!(urls, vec![
+            "http://localhost:9200/test/_doc/1?if_seq_no=3&if_primary_term=1&require_alias=false",
+            "http://localhost:9200/test/_doc/1?version=8&version_type=external_gte",
+        ]);
     }
 }
@@ -1,97 +1,246 @@
+use reqwest::StatusCode;
 use serde::Deserialize;
 use std::error::Error;
 use std::fmt;
 
-/// Index creation failures
-///
-#[derive(Deserialize, Debug)]
-pub struct ESClientCreateIndexFail {
-    error: ESClientCreateIndexError,
-    status: u16,
-}
+use crate::transport::TransportError;
 
-impl fmt::Display for ESClientCreateIndexFail {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "error: {:?}, status: {}", self.error, self.status)
-    }
+/// Errors returned by the EsClient.
+#[derive(Debug)]
+pub enum EsError {
+    /// Request could not be sent or the response could not be read.
+    Transport(TransportError),
+    /// Request timed out waiting for the response. Connect timeouts are reported as Transport errors.
+    Timeout(TransportError),
+    /// Elasticsearch responded with an unexpected status and a body that is not an ES error.
+    Http {
+        status: u16,
+        body: String,
+    },
+    /// Elasticsearch responded with an error.
+    Api {
+        status: u16,
+        error_type: String,
+        reason: Option<String>,
+        root_cause: Vec<EsErrorCause>,
+        caused_by: Option<Box<EsErrorCause>>,
+    },
+    /// Response body could not be deserialized.
+    Deserialize(serde_json::Error),
+    /// Document id is required for the method, use `DocId::assigned(&str)`.
+    MissingId,
+    /// Elasticsearch version is not supported by the client.
+    VersionUnsupported(String),
+    /// Index or document target can not be used with the request or version of Elasticsearch.
+    InvalidTarget(String),
+    /// Elasticsearch sent deprecation warnings with a successful response, see `DeprecationMode::Fail`.
+    Deprecated(Vec<String>),
+    /// Request was rejected by a middleware before it was sent or after its response was received.
+    Rejected(String),
+    /// Client is misconfigured or used in an unsupported way.
+    Config(String),
+    /// Runtime used to block on a request could not be created, or a file could not be read.
+    Io(std::io::Error),
 }
 
-impl Error for ESClientCreateIndexFail {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
-    }
+/// Cause of an error reported by Elasticsearch.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EsErrorCause {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub reason: Option<String>,
+    pub index: Option<String>,
+    pub caused_by: Option<Box<EsErrorCause>>,
 }
 
+/// Error body sent back by Elasticsearch.
 #[derive(Deserialize, Debug)]
-struct ESClientCreateIndexError {
-    root_cause: Vec<ESClientCreateIndexFailMetadata>,
+struct EsErrorResponse {
+    error: EsErrorBody,
 }
 
 #[derive(Deserialize, Debug)]
-struct ESClientCreateIndexFailMetadata {
+struct EsErrorBody {
     #[serde(rename = "type")]
-    error_type: String,
-    reason: String,
-    index_uuid: String,
-    index: String,
+    error_type: Option<String>,
+    reason: Option<String>,
+    #[serde(default)]
+    root_cause: Vec<EsErrorCause>,
+    caused_by: Option<Box<EsErrorCause>>,
 }
 
-/// ES Generic Failures
-///
-/// includes: search, and document creation/update
-#[derive(Deserialize, Debug)]
-pub struct ESGenericFail {
-    error: ESGenericError,
-    status: u16,
+impl EsError {
+    /// Create the error for a response that was not successful.
+    ///
+    /// Returns `EsError::Api` if the body is an ES error, otherwise `EsError::Http`.
+    pub fn from_response(status: StatusCode, body: String) -> EsError {
+        let status = status.as_u16();
+        match serde_json::from_str::<EsErrorResponse>(&body) {
+            Ok(EsErrorResponse { error }) => {
+                let EsErrorBody { error_type, reason, root_cause, caused_by } = error;
+                // Some errors only report the type on their root causes.
+                let first_cause = root_cause.first();
+                let error_type = error_type
+                    .or_else(|| first_cause.map(|cause| cause.error_type.clone()))
+                    .unwrap_or_default();
+                let reason = reason
+                    .or_else(|| first_cause.and_then(|cause| cause.reason.clone()));
+
+                EsError::Api {
+                    status,
+                    error_type,
+                    reason,
+                    root_cause,
+                    caused_by,
+                }
+            },
+            Err(_) => EsError::Http { status, body },
+        }
+    }
+
+    /// Http status of the response, if Elasticsearch responded.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            EsError::Http { status, .. } => Some(*status),
+            EsError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
 }
 
-impl fmt::Display for ESGenericFail {
+impl fmt::Display for EsError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "error: {:?}, status: {}", self.error, self.status)
+        match self {
+            EsError::Transport(error) => write!(f, "transport error: {}", error),
+            EsError::Timeout(error) => write!(f, "request timed out: {}", error),
+            EsError::Http { status, body } => write!(f, "status: {}, body: {}", status, body),
+            EsError::Api { status, error_type, reason, .. } => match reason {
+                Some(reason) => write!(f, "status: {}, type: {}, reason: {}", status, error_type, reason),
+                None => write!(f, "status: {}, type: {}", status, error_type),
+            },
+            EsError::Deserialize(error) => write!(f, "failed to deserialize response: {}", error),
+            EsError::MissingId => write!(f, "Document id is required for this method! Please use DocId::assigned(&str)"),
+            EsError::VersionUnsupported(version) => write!(f, "Elasticsearch version {} not currently supported. Please open up a ticket.", version),
+            EsError::InvalidTarget(reason) => write!(f, "invalid target: {}", reason),
+            EsError::Deprecated(warnings) => write!(f, "deprecated usage: {}", warnings.join("; ")),
+            EsError::Rejected(reason) => write!(f, "request rejected: {}", reason),
+            EsError::Config(reason) => write!(f, "invalid client configuration: {}", reason),
+            EsError::Io(error) => write!(f, "io error: {}", error),
+        }
     }
 }
 
-impl Error for ESGenericFail {
+impl Error for EsError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+        match self {
+            EsError::Transport(error) => Some(error),
+            EsError::Timeout(error) => Some(error),
+            EsError::Deserialize(error) => Some(error),
+            EsError::Io(error) => Some(error),
+            _ => None,
+        }
     }
 }
 
-#[derive(Deserialize, Debug)]
-struct ESGenericError {
-    root_cause: Vec<ESGenericFailMetadata>,
+impl From<TransportError> for EsError {
+    fn from(error: TransportError) -> EsError {
+        match error.is_timeout() {
+            true => EsError::Timeout(error),
+            false => EsError::Transport(error),
+        }
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct ESGenericFailMetadata {
-    #[serde(rename = "type")]
-    error_type: String,
-    reason: String,
+impl From<reqwest::Error> for EsError {
+    fn from(error: reqwest::Error) -> EsError {
+        TransportError::from(error).into()
+    }
 }
 
-#[derive(Deserialize, Debug)]
-pub struct ESMissingId {
-    status: u16,
-    reason: String,
+impl From<serde_json::Error> for EsError {
+    fn from(error: serde_json::Error) -> EsError {
+        EsError::Deserialize(error)
+    }
 }
 
-impl ESMissingId {
-    pub fn new() -> Self {
-        ESMissingId{
-            reason: "Document id is required for this method! Please use DocId::Assigned(&str)".to_owned(),
-            status: 400
-        }
+impl From<std::io::Error> for EsError {
+    fn from(error: std::io::Error) -> EsError {
+        EsError::Io(error)
     }
 }
 
-impl fmt::Display for ESMissingId {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "reason: {}, status: {}", self.reason, self.status)
+#[cfg(test)]
+mod tests {
+    use super::{EsError, EsErrorCause};
+    use reqwest::StatusCode;
+
+    #[test]
+    fn api_error_from_response() {
+        let body = r#"{
+            "error": {
+                "root_cause": [{
+                    "type": "es_rejected_execution_exception",
+                    "reason": "rejected execution"
+                }],
+                "type": "search_phase_execution_exception",
+                "reason": "all shards failed",
+                "caused_by": {
+                    "type": "es_rejected_execution_exception",
+                    "reason": "rejected execution"
+                }
+            },
+            "status": 429
+        }"#;
+
+        let cause = EsErrorCause {
+            error_type: "es_rejected_execution_exception".to_owned(),
+            reason: Some("rejected execution".to_owned()),
+            index: None,
+            caused_by: None,
+        };
+        match EsError::from_response(StatusCode::TOO_MANY_REQUESTS, body.to_owned()) {
+            EsError::Api { status, error_type, reason, root_cause, caused_by } => {
+                assert_eq!(status, 429);
+                assert_eq!(error_type, "search_phase_execution_exception");
+                assert_eq!(reason, Some("all shards failed".to_owned()));
+                assert_eq!(root_cause, vec![cause.clone()]);
+                assert_eq!(caused_by, Some(Box::new(cause)));
+            },
+            error => panic!("Unexpected error: {:?}", error),
+        }
     }
-}
 
-impl Error for ESMissingId {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+    #[test]
+    fn api_error_type_from_root_cause() {
+        let body = r#"{
+            "status": 400,
+            "error": {
+                "root_cause": [{
+                    "type": "index_create_failure",
+                    "reason": "could not create index, bad input",
+                    "index_uuid": "joiesjfkoe",
+                    "index": "test"
+                }]
+            }
+        }"#;
+
+        match EsError::from_response(StatusCode::BAD_REQUEST, body.to_owned()) {
+            EsError::Api { error_type, reason, root_cause, .. } => {
+                assert_eq!(error_type, "index_create_failure");
+                assert_eq!(reason, Some("could not create index, bad input".to_owned()));
+                assert_eq!(root_cause[0].index, Some("test".to_owned()));
+            },
+            error => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn http_error_from_response() {
+        let error = EsError::from_response(StatusCode::BAD_GATEWAY, "Bad Gateway".to_owned());
+        assert_eq!(error.status(), Some(502));
+        match error {
+            EsError::Http { body, .. } => assert_eq!(body, "Bad Gateway"),
+            error => panic!("Unexpected error: {:?}", error),
+        }
     }
 }
@@ -1,10 +1,13 @@
 use reqwest::StatusCode;
 use serde::Deserialize;
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use crate::client::EsClient;
+use crate::request::{common_options, time_value, ActiveShards, CommonOptions, EsRequest, WriteOptions};
+use crate::response::Response;
 use crate::utils::serialize_response;
-use crate::errors::ESClientCreateIndexFail;
+use crate::errors::EsError;
+use crate::trace::{self, op_span};
 
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct EsIndexCreateSuccess {
@@ -19,30 +22,70 @@ impl fmt::Display for EsIndexCreateSuccess {
     }
 }
 
-pub async fn create_index_req(client: &EsClient, index: &str) -> Result<EsIndexCreateSuccess, Box<dyn std::error::Error>> {
-    let res = client.put(Some(index), None)
-        .send()
-        .await?;
-
-    let res = match res.status() {
-        StatusCode::OK => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsIndexCreateSuccess>(&text)?;
-            data
-        },
-        StatusCode::BAD_REQUEST => { 
-            let text = res.text().await?;
-            let data = serialize_response::<ESClientCreateIndexFail>(&text)?;
-            return Err(Box::new(data));
-        },
-        _ => panic!("Request failed in an unexpected way..."),
-    };
-    Ok(res)
+/// Options of the create index request.
+#[derive(Debug, Clone, Default)]
+pub struct CreateIndexOptions {
+    master_timeout: Option<Duration>,
+    write: WriteOptions,
+    common: CommonOptions,
+}
+
+common_options!(CreateIndexOptions);
+
+impl CreateIndexOptions {
+    /// Create new CreateIndexOptions.
+    pub fn new() -> CreateIndexOptions {
+        CreateIndexOptions::default()
+    }
+
+    /// Set how long the cluster waits for the index to be created, sent as `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> CreateIndexOptions {
+        self.write.timeout = Some(timeout);
+        self
+    }
+
+    /// Set how long the cluster waits for the master node, sent as `master_timeout`.
+    pub fn master_timeout(mut self, master_timeout: Duration) -> CreateIndexOptions {
+        self.master_timeout = Some(master_timeout);
+        self
+    }
+
+    /// Set the shard copies that must be active before the request proceeds, sent as
+    /// `wait_for_active_shards`.
+    pub fn wait_for_active_shards(mut self, wait_for_active_shards: ActiveShards) -> CreateIndexOptions {
+        self.write.wait_for_active_shards = Some(wait_for_active_shards);
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(master_timeout) = self.master_timeout {
+            request = request.param("master_timeout", time_value(master_timeout));
+        }
+        self.common.apply(self.write.apply(request))
+    }
+}
+
+pub async fn create_index_req(client: &EsClient, index: &str, options: CreateIndexOptions) -> Result<Response<EsIndexCreateSuccess>, EsError> {
+    op_span!("create_index").run(client, async move {
+        client.detect_version().await?;
+        trace::record_index(index);
+
+        let request = options.apply(client.put_request(Some(index), None));
+        let res = client.send_request(request).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => Ok(Response::new(serialize_response::<EsIndexCreateSuccess>(&text)?, res)),
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{EsIndexCreateSuccess, create_index_req};
+    use super::{EsIndexCreateSuccess, CreateIndexOptions, create_index_req};
     use crate::client::EsClient;
     use mockito::mock;
     use tokio::runtime::Runtime;
@@ -83,14 +126,14 @@ mod tests {
             .create();
 
         let client = EsClient::new("http://127.0.0.1", 1234);
-        let res = create_index_req(&client, "test");
+        let res = create_index_req(&client, "test", CreateIndexOptions::default());
         let res = rt.block_on(res);
         let expected_res = EsIndexCreateSuccess {
             acknowledged: true,
             shards_acknowledged: true,
             index: "test".to_owned(),
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -135,13 +178,12 @@ mod tests {
             .create();
 
         let client = EsClient::new("http://127.0.0.1", 1234);
-        let res = create_index_req(&client, "test");
+        let res = create_index_req(&client, "test", CreateIndexOptions::default());
         let res = rt.block_on(res);
-        assert_eq!(res.is_err(), true);
+        assert!(res.is_err());
     }
 
     #[test]
-    #[should_panic]
     fn unexpected_error() {
         let rt = Runtime::new().unwrap();
         let _client_mock = mock("GET", "/")
@@ -183,7 +225,8 @@ mod tests {
             .create();
 
         let client = EsClient::new("http://127.0.0.1", 1234);
-        let res = create_index_req(&client, "test");
-        let _ = rt.block_on(res);
+        let res = create_index_req(&client, "test", CreateIndexOptions::default());
+        let res = rt.block_on(res);
+        assert_eq!(res.unwrap_err().status(), Some(500));
     }
 }
@@ -1,28 +1,35 @@
-use reqwest;
+use reqwest::StatusCode;
 use serde::Deserialize;
 
 use crate::client::EsClient;
+use crate::errors::EsError;
+use crate::response::Response;
+use crate::utils::serialize_response;
+use crate::trace::op_span;
 
+/// Basic information about the cluster returned by the info request.
 #[derive(Deserialize, Debug)]
 pub struct EsInfo {
-    name: String,
-    cluster_name: String,
-    cluster_uuid: String,
-    version: Version,
-    tagline: String,
+    pub name: String,
+    pub cluster_name: String,
+    pub cluster_uuid: String,
+    pub version: EsInfoVersion,
+    pub tagline: String,
 }
 
+/// Version details of the cluster, `distribution` is only sent by OpenSearch.
 #[derive(Deserialize, Debug)]
-struct Version {
-    number: String,
-    build_flavor: String,
-    build_type: String,
-    build_hash: String,
-    build_date: String,
-    build_snapshot: bool,
-    lucene_version: String,
-    minimum_wire_compatibility_version: String,
-    minimum_index_compatibility_version: String,
+pub struct EsInfoVersion {
+    pub number: String,
+    pub distribution: Option<String>,
+    pub build_flavor: Option<String>,
+    pub build_type: Option<String>,
+    pub build_hash: String,
+    pub build_date: String,
+    pub build_snapshot: bool,
+    pub lucene_version: String,
+    pub minimum_wire_compatibility_version: String,
+    pub minimum_index_compatibility_version: String,
 }
 
 impl EsInfo {
@@ -31,18 +38,23 @@ impl EsInfo {
     }
 }
 
-pub async fn es_info_req(client: &EsClient) -> reqwest::Result<EsInfo> {
-    let res = client.get(None)
-        .send()
-        .await?
-        .json::<EsInfo>()
-        .await?;
-    Ok(res)
+pub async fn es_info_req(client: &EsClient) -> Result<Response<EsInfo>, EsError> {
+    op_span!("info").run(client, async move {
+        let res = client.send_request(client.get_request(None)).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => Ok(Response::new(serialize_response::<EsInfo>(&text)?, res)),
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{EsInfo, Version};
+    use super::{EsInfo, EsInfoVersion};
 
     #[test]
     fn test_get_version_string() {
@@ -50,10 +62,11 @@ mod tests {
             name: "test".to_owned(),
             cluster_name: "test".to_owned(),
             cluster_uuid: "test".to_owned(),
-            version: Version {
+            version: EsInfoVersion {
                 number: "test".to_owned(),
-                build_flavor: "test".to_owned(),
-                build_type: "test".to_owned(),
+                distribution: None,
+                build_flavor: Some("test".to_owned()),
+                build_type: Some("test".to_owned()),
                 build_hash: "test".to_owned(),
                 build_date: "test".to_owned(),
                 build_snapshot: false,
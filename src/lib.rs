@@ -1,8 +1,24 @@
+pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod cat;
 pub mod client;
+pub mod config;
 pub mod doc;
 pub mod errors;
 pub mod index;
 pub mod info;
+pub mod metrics;
+pub mod middleware;
+pub mod pattern;
+pub mod pool;
+pub mod request;
+pub mod response;
+pub mod retry;
 pub mod search;
+pub mod sniff;
+pub mod tls;
+mod trace;
+pub mod transport;
 pub mod utils;
+pub mod version;
@@ -0,0 +1,172 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::errors::EsError;
+
+/// Hook recording metrics of the API calls made with the EsClient, such as request counts,
+/// latency histograms and error rates, as the application sees them.
+pub trait Metrics: Send + Sync {
+    /// Record a completed API call, whether it succeeded or failed.
+    fn record(&self, metrics: &RequestMetrics<'_>);
+}
+
+/// Metrics of an API call, including every retry. A version lookup or sniff triggered by the
+/// call is recorded as a call of its own, `info` or `sniff`.
+#[derive(Debug)]
+pub struct RequestMetrics<'a> {
+    /// Operation of the call, such as `search`, `index`, `cat.aliases` or `send` for raw requests.
+    pub operation: &'a str,
+    /// Targets of the call, `None` for calls without an index such as `info`.
+    pub index: Option<&'a str>,
+    /// Status of the last response, `None` if no node responded.
+    pub status: Option<u16>,
+    /// Time from the start of the call until the response was read.
+    pub duration: Duration,
+    /// Bytes of the request bodies sent, counting each attempt.
+    pub bytes_sent: u64,
+    /// Bytes of the response bodies received, counting each attempt.
+    pub bytes_received: u64,
+    /// How many times the request was sent again.
+    pub retries: u32,
+    pub error: Option<&'a EsError>,
+}
+
+/// Metrics hook of the client, if set.
+#[derive(Clone, Default)]
+pub(crate) struct MetricsRecorder {
+    metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl MetricsRecorder {
+    pub(crate) fn new(metrics: Arc<dyn Metrics>) -> MetricsRecorder {
+        MetricsRecorder { metrics: Some(metrics) }
+    }
+
+    pub(crate) fn record(&self, operation: &str, stats: &OpStats, duration: Duration, error: Option<&EsError>) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&RequestMetrics {
+                operation,
+                index: stats.index.as_deref(),
+                status: stats.status,
+                duration,
+                bytes_sent: stats.bytes_sent,
+                bytes_received: stats.bytes_received,
+                retries: stats.retries,
+                error,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for MetricsRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MetricsRecorder {{ enabled: {} }}", self.metrics.is_some())
+    }
+}
+
+/// Stats of the API call running on the task, updated as its requests are sent.
+#[derive(Debug, Default)]
+pub(crate) struct OpStats {
+    pub(crate) index: Option<String>,
+    pub(crate) status: Option<u16>,
+    pub(crate) bytes_sent: u64,
+    pub(crate) bytes_received: u64,
+    pub(crate) retries: u32,
+}
+
+tokio::task_local! {
+    static STATS: RefCell<OpStats>;
+}
+
+/// Run the API call, collecting its stats.
+pub(crate) async fn collect<T, F: Future<Output = T>>(operation: F) -> (T, OpStats) {
+    STATS.scope(RefCell::new(OpStats::default()), async move {
+        let res = operation.await;
+        (res, STATS.with(RefCell::take))
+    }).await
+}
+
+/// Update the stats of the API call running on the task, if any.
+pub(crate) fn update<F: FnOnce(&mut OpStats)>(update: F) {
+    let _ = STATS.try_with(|stats| update(&mut stats.borrow_mut()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, RequestMetrics};
+    use crate::{
+        client::{EsClientBuilder, IndexPattern, Method, Version},
+        retry::RetryPolicy,
+        transport::{InMemoryTransport, TransportResponse},
+    };
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+    use tokio::runtime::Runtime;
+
+    /// Operation, index, status, bytes sent and received, retries and whether it failed.
+    type Recorded = (String, Option<String>, Option<u16>, u64, u64, u32, bool);
+
+    #[derive(Default)]
+    struct Recorder {
+        recorded: Arc<Mutex<Vec<Recorded>>>,
+    }
+
+    impl Metrics for Recorder {
+        fn record(&self, metrics: &RequestMetrics<'_>) {
+            self.recorded.lock().unwrap().push((
+                metrics.operation.to_owned(),
+                metrics.index.map(str::to_owned),
+                metrics.status,
+                metrics.bytes_sent,
+                metrics.bytes_received,
+                metrics.retries,
+                metrics.error.is_some(),
+            ));
+        }
+    }
+
+    const COUNT: &str = r#"{"count": 3, "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0}}"#;
+    const ERROR: &str = r#"{"error": {"type": "illegal_argument_exception"}}"#;
+
+    #[test]
+    fn record_request_metrics() {
+        let rt = Runtime::new().unwrap();
+        let attempts = AtomicUsize::new(0);
+        let transport = InMemoryTransport::new(move |request| match (request.method().as_str(), attempts.fetch_add(1, Ordering::SeqCst)) {
+            ("POST", 0) => Ok(TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "")),
+            ("POST", _) => Ok(TransportResponse::new(StatusCode::OK, COUNT)),
+            _ => Ok(TransportResponse::new(StatusCode::BAD_REQUEST, ERROR)),
+        });
+        let recorder = Recorder::default();
+        let recorded = recorder.recorded.clone();
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)))
+            .transport(transport)
+            .metrics(recorder)
+            .build()
+            .unwrap();
+
+        rt.block_on(client.count(IndexPattern::index("test"), json!({}))).unwrap();
+        assert!(rt.block_on(client.create_index("test")).is_err());
+        assert!(rt.block_on(client.send(Method::GET, "_cluster/health", &[], None)).is_err());
+
+        assert_eq!(*recorded.lock().unwrap(), vec![
+            ("count".to_owned(), Some("test".to_owned()), Some(200), 4, COUNT.len() as u64, 1, false),
+            ("create_index".to_owned(), Some("test".to_owned()), Some(400), 0, ERROR.len() as u64, 0, true),
+            ("send".to_owned(), None, Some(400), 0, ERROR.len() as u64, 0, true),
+        ]);
+    }
+}
@@ -0,0 +1,150 @@
+use futures::future::BoxFuture;
+use std::{fmt, sync::Arc};
+
+use crate::{
+    errors::EsError,
+    transport::{TransportRequest, TransportResponse},
+};
+
+/// Hooks run around every request sent by the EsClient, such as adding headers, logging
+/// payloads or rejecting requests.
+///
+/// Hooks run on each attempt, so a retried request goes through them again.
+pub trait Middleware: Send + Sync {
+    /// Change the request before it is sent, or return an error, such as `EsError::Rejected`,
+    /// to reject it.
+    fn before_send<'a>(&'a self, _request: &'a mut TransportRequest) -> BoxFuture<'a, Result<(), EsError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Inspect the response of the request, whatever its status, or return an error to fail the request.
+    fn after_receive<'a>(&'a self, _request: &'a TransportRequest, _response: &'a TransportResponse) -> BoxFuture<'a, Result<(), EsError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Middleware of the client, run in the order they were added before sending and in the
+/// reverse order after receiving.
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareChain {
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl MiddlewareChain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.middleware.push(middleware);
+    }
+
+    pub(crate) async fn before_send(&self, request: &mut TransportRequest) -> Result<(), EsError> {
+        for middleware in &self.middleware {
+            middleware.before_send(request).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn after_receive(&self, request: &TransportRequest, response: &TransportResponse) -> Result<(), EsError> {
+        for middleware in self.middleware.iter().rev() {
+            middleware.after_receive(request, response).await?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MiddlewareChain {{ middleware: {} }}", self.middleware.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Middleware;
+    use crate::{
+        client::{EsClientBuilder, IndexPattern, Method, Version},
+        doc::DocId,
+        errors::EsError,
+        transport::{InMemoryTransport, TransportRequest, TransportResponse},
+    };
+    use futures::future::BoxFuture;
+    use reqwest::{header::HeaderValue, StatusCode};
+    use std::sync::{Arc, Mutex};
+    use tokio::runtime::Runtime;
+
+    /// Adds the opaque id header and records the hooks it runs.
+    struct OpaqueId {
+        id: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for OpaqueId {
+        fn before_send<'a>(&'a self, request: &'a mut TransportRequest) -> BoxFuture<'a, Result<(), EsError>> {
+            Box::pin(async move {
+                request.headers_mut().insert("x-opaque-id", HeaderValue::from_static(self.id));
+                self.calls.lock().unwrap().push(format!("before {}", self.id));
+                Ok(())
+            })
+        }
+
+        fn after_receive<'a>(&'a self, _request: &'a TransportRequest, response: &'a TransportResponse) -> BoxFuture<'a, Result<(), EsError>> {
+            Box::pin(async move {
+                self.calls.lock().unwrap().push(format!("after {} {}", self.id, response.status().as_u16()));
+                Ok(())
+            })
+        }
+    }
+
+    /// Rejects requests that can change the cluster.
+    struct ReadOnly;
+
+    impl Middleware for ReadOnly {
+        fn before_send<'a>(&'a self, request: &'a mut TransportRequest) -> BoxFuture<'a, Result<(), EsError>> {
+            Box::pin(async move {
+                match *request.method() {
+                    Method::GET | Method::HEAD => Ok(()),
+                    _ => Err(EsError::Rejected(format!("{} {} in read-only mode", request.method(), request.url()))),
+                }
+            })
+        }
+    }
+
+    fn transport() -> Arc<InMemoryTransport> {
+        Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, "{}"))))
+    }
+
+    #[test]
+    fn run_middleware_in_order() {
+        let rt = Runtime::new().unwrap();
+        let transport = transport();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .middleware(OpaqueId { id: "first", calls: calls.clone() })
+            .middleware(OpaqueId { id: "second", calls: calls.clone() })
+            .build()
+            .unwrap();
+
+        rt.block_on(client.send(Method::GET, "_cluster/health", &[], None)).unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["before first", "before second", "after second 200", "after first 200"]);
+        assert_eq!(transport.requests()[0].headers()["x-opaque-id"], "second");
+    }
+
+    #[test]
+    fn reject_request_before_send() {
+        let rt = Runtime::new().unwrap();
+        let transport = transport();
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .middleware(ReadOnly)
+            .build()
+            .unwrap();
+
+        match rt.block_on(client.delete_doc(IndexPattern::index("test"), DocId::assigned("1"))) {
+            Err(EsError::Rejected(reason)) => assert_eq!(reason, "DELETE http://localhost:9200/test/_doc/1 in read-only mode"),
+            res => panic!("Unexpected response: {:?}", res),
+        }
+        assert!(transport.requests().is_empty());
+        assert!(rt.block_on(client.send(Method::GET, "_cluster/health", &[], None)).is_ok());
+    }
+}
@@ -0,0 +1,302 @@
+use std::borrow::Cow;
+
+use crate::errors::EsError;
+
+/// Indices targeted by a request, a single index with an optional type, or several targets.
+///
+/// Names are borrowed or owned, so requests can be built from `String`s and moved into
+/// spawned tasks, such as `IndexPattern::index(name)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexPattern<'a> {
+    Index(Cow<'a, str>),
+    IndexType(Cow<'a, str>, Cow<'a, str>),
+    /// Several targets of a multi-target request such as search, combined in order.
+    Targets(Vec<Target<'a>>),
+    /// Every index of the cluster, sent as `_all`.
+    All,
+}
+
+/// Target of a multi-target request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target<'a> {
+    /// Index, alias or data stream.
+    Index(Cow<'a, str>),
+    /// Name with `*` wildcards, such as `logs-*`.
+    Wildcard(Cow<'a, str>),
+    /// Name or wildcard removed from the targets before it, sent as `-logs-old`.
+    Exclude(Cow<'a, str>),
+    /// Name or wildcard on a remote cluster, sent as `cluster:index`.
+    Remote(Cow<'a, str>, Cow<'a, str>),
+    /// Date math name without the angle brackets, such as `logs-{now/d}`, sent as `<logs-{now/d}>`.
+    DateMath(Cow<'a, str>),
+}
+
+impl<'a> IndexPattern<'a> {
+    /// Single index, alias or data stream.
+    pub fn index<S: Into<Cow<'a, str>>>(index: S) -> IndexPattern<'a> {
+        IndexPattern::Index(index.into())
+    }
+
+    /// Single index with a document type, for clusters before 7.0.
+    pub fn index_type<S: Into<Cow<'a, str>>, T: Into<Cow<'a, str>>>(index: S, doc_type: T) -> IndexPattern<'a> {
+        IndexPattern::IndexType(index.into(), doc_type.into())
+    }
+
+    /// Copy the borrowed names, so the pattern no longer borrows them.
+    pub fn into_owned(self) -> IndexPattern<'static> {
+        match self {
+            IndexPattern::Index(index) => IndexPattern::Index(Cow::Owned(index.into_owned())),
+            IndexPattern::IndexType(index, doc_type) => IndexPattern::IndexType(
+                Cow::Owned(index.into_owned()),
+                Cow::Owned(doc_type.into_owned()),
+            ),
+            IndexPattern::Targets(targets) => IndexPattern::Targets(targets.into_iter().map(Target::into_owned).collect()),
+            IndexPattern::All => IndexPattern::All,
+        }
+    }
+
+    /// Index and type of a request to a single index, such as indexing a document.
+    pub(crate) fn single_target(&self) -> Result<(&str, Option<&str>), EsError> {
+        match self {
+            IndexPattern::Index(index) => {
+                validate_name(index, false)?;
+                Ok((index, None))
+            },
+            IndexPattern::IndexType(index, doc_type) => {
+                validate_name(index, false)?;
+                Ok((index, Some(doc_type)))
+            },
+            IndexPattern::Targets(_) | IndexPattern::All => Err(EsError::InvalidTarget(
+                "Request targets a single index, use IndexPattern::Index instead.".to_owned()
+            )),
+        }
+    }
+
+    /// Comma separated targets and the type of a multi-target request, such as search.
+    pub(crate) fn multi_target(&self) -> Result<(String, Option<&str>), EsError> {
+        match self {
+            // A name can still hold an expression such as `logs-*,-logs-old`, each of its
+            // targets is validated as if it was written with Targets.
+            IndexPattern::Index(index) => Ok((validate_expression(index)?, None)),
+            IndexPattern::IndexType(index, doc_type) => Ok((validate_expression(index)?, Some(doc_type))),
+            IndexPattern::Targets(targets) => Ok((join_targets(targets)?, None)),
+            IndexPattern::All => Ok(("_all".to_owned(), None)),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for IndexPattern<'a> {
+    fn from(index: &'a str) -> IndexPattern<'a> {
+        IndexPattern::index(index)
+    }
+}
+
+impl From<String> for IndexPattern<'static> {
+    fn from(index: String) -> IndexPattern<'static> {
+        IndexPattern::index(index)
+    }
+}
+
+impl<'a> Target<'a> {
+    /// Index, alias or data stream.
+    pub fn index<S: Into<Cow<'a, str>>>(index: S) -> Target<'a> {
+        Target::Index(index.into())
+    }
+
+    /// Name with `*` wildcards.
+    pub fn wildcard<S: Into<Cow<'a, str>>>(pattern: S) -> Target<'a> {
+        Target::Wildcard(pattern.into())
+    }
+
+    /// Name or wildcard removed from the targets before it.
+    pub fn exclude<S: Into<Cow<'a, str>>>(pattern: S) -> Target<'a> {
+        Target::Exclude(pattern.into())
+    }
+
+    /// Name or wildcard on a remote cluster.
+    pub fn remote<S: Into<Cow<'a, str>>, T: Into<Cow<'a, str>>>(cluster: S, pattern: T) -> Target<'a> {
+        Target::Remote(cluster.into(), pattern.into())
+    }
+
+    /// Date math name without the angle brackets.
+    pub fn date_math<S: Into<Cow<'a, str>>>(expression: S) -> Target<'a> {
+        Target::DateMath(expression.into())
+    }
+
+    /// Copy the borrowed names, so the target no longer borrows them.
+    pub fn into_owned(self) -> Target<'static> {
+        let owned = |name: Cow<'a, str>| -> Cow<'static, str> { Cow::Owned(name.into_owned()) };
+        match self {
+            Target::Index(index) => Target::Index(owned(index)),
+            Target::Wildcard(pattern) => Target::Wildcard(owned(pattern)),
+            Target::Exclude(pattern) => Target::Exclude(owned(pattern)),
+            Target::Remote(cluster, pattern) => Target::Remote(owned(cluster), owned(pattern)),
+            Target::DateMath(expression) => Target::DateMath(owned(expression)),
+        }
+    }
+
+    fn to_target(&self) -> Result<String, EsError> {
+        match self {
+            Target::Index(index) => {
+                validate_name(index, false)?;
+                Ok(index.to_string())
+            },
+            Target::Wildcard(pattern) => {
+                validate_name(pattern, true)?;
+                Ok(pattern.to_string())
+            },
+            Target::Exclude(pattern) => {
+                validate_name(pattern, true)?;
+                Ok(format!("-{}", pattern))
+            },
+            Target::Remote(cluster, pattern) => {
+                if cluster.is_empty() || cluster.contains([':', ',']) {
+                    return Err(EsError::InvalidTarget(format!("Invalid remote cluster name {}.", cluster)));
+                }
+                validate_name(pattern, true)?;
+                Ok(format!("{}:{}", cluster, pattern))
+            },
+            Target::DateMath(expression) => {
+                if !expression.contains('{') || expression.starts_with('<') || expression.contains(',') {
+                    return Err(EsError::InvalidTarget(
+                        format!("Invalid date math name {}, expected a name such as logs-{{now/d}}.", expression)
+                    ));
+                }
+                Ok(format!("<{}>", expression))
+            },
+        }
+    }
+}
+
+/// Validate the targets and join them, separated by commas.
+fn join_targets(targets: &[Target]) -> Result<String, EsError> {
+    if targets.is_empty() {
+        return Err(EsError::InvalidTarget("At least one target is required.".to_owned()));
+    }
+    if let Some(Target::Exclude(_)) = targets.first() {
+        return Err(EsError::InvalidTarget("Exclusions must follow the targets they are removed from.".to_owned()));
+    }
+
+    let targets = targets.iter()
+        .map(Target::to_target)
+        .collect::<Result<Vec<String>, EsError>>()?;
+    Ok(targets.join(","))
+}
+
+/// Validate each target of a comma separated expression, such as `logs-*,-logs-old`, with the
+/// rules of the Target it is written as.
+fn validate_expression(expression: &str) -> Result<String, EsError> {
+    if expression == "_all" {
+        return Ok(expression.to_owned());
+    }
+
+    let targets: Vec<Target> = expression.split(',')
+        .map(|target| {
+            if let Some(pattern) = target.strip_prefix('-') {
+                Target::exclude(pattern)
+            } else if let Some(expression) = target.strip_prefix('<').and_then(|target| target.strip_suffix('>')) {
+                Target::date_math(expression)
+            } else if let Some(colon) = target.find(':') {
+                Target::remote(&target[..colon], &target[colon + 1..])
+            } else if target.contains('*') {
+                Target::wildcard(target)
+            } else {
+                Target::index(target)
+            }
+        })
+        .collect();
+    join_targets(&targets)
+}
+
+/// Check the name follows the rules of index names, allowing `*` wildcards if set.
+fn validate_name(name: &str, wildcard: bool) -> Result<(), EsError> {
+    let invalid = |reason: &str| Err(EsError::InvalidTarget(format!("Invalid index name {}: {}.", name, reason)));
+
+    if name.is_empty() {
+        return invalid("name is empty");
+    }
+    if name == "." || name == ".." {
+        return invalid("name can not be . or ..");
+    }
+    if name.starts_with(['-', '_', '+']) {
+        return invalid("name can not start with -, _ or +");
+    }
+    if let Some(c) = name.chars().find(|c| r#"\/?"<>|,#: "#.contains(*c)) {
+        return invalid(&format!("name can not contain {:?}", c));
+    }
+    if !wildcard && name.contains('*') {
+        return invalid("use Target::Wildcard for wildcards");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexPattern, Target};
+
+    #[test]
+    fn single_targets() {
+        assert_eq!(IndexPattern::index("test").single_target().unwrap(), ("test", None));
+        assert_eq!(IndexPattern::index_type(".hidden", "doc").single_target().unwrap(), (".hidden", Some("doc")));
+        assert!(IndexPattern::index("logs-*").single_target().is_err());
+        assert!(IndexPattern::index("").single_target().is_err());
+        assert!(IndexPattern::index("a,b").single_target().is_err());
+        assert!(IndexPattern::All.single_target().is_err());
+        assert!(IndexPattern::Targets(vec![Target::index("test")]).single_target().is_err());
+
+        let index = String::from("test");
+        let owned = IndexPattern::index(index.as_str()).into_owned();
+        drop(index);
+        assert_eq!(owned, IndexPattern::index(String::from("test")));
+    }
+
+    #[test]
+    fn multi_targets() {
+        let targets = IndexPattern::Targets(vec![
+            Target::index("test"),
+            Target::wildcard("logs-*"),
+            Target::exclude("logs-old"),
+            Target::remote("eu", "logs-*"),
+            Target::date_math("logs-{now/d}"),
+        ]);
+        assert_eq!(targets.multi_target().unwrap().0, "test,logs-*,-logs-old,eu:logs-*,<logs-{now/d}>");
+        assert_eq!(IndexPattern::All.multi_target().unwrap().0, "_all");
+        assert_eq!(IndexPattern::index_type("logs-*", "doc").multi_target().unwrap(), ("logs-*".to_owned(), Some("doc")));
+        assert_eq!(IndexPattern::index("logs-*").multi_target().unwrap().0, "logs-*");
+        assert_eq!(IndexPattern::index("a,b,-c").multi_target().unwrap().0, "a,b,-c");
+        assert_eq!(IndexPattern::index("_all").multi_target().unwrap().0, "_all");
+        assert_eq!(
+            IndexPattern::index("test,logs-*,-logs-old,eu:logs-*,<logs-{now/d}>").multi_target().unwrap().0,
+            "test,logs-*,-logs-old,eu:logs-*,<logs-{now/d}>",
+        );
+    }
+
+    #[test]
+    fn invalid_multi_target_expressions() {
+        let invalid = |expression| IndexPattern::index(expression).multi_target().is_err();
+        assert!(invalid(""));
+        assert!(invalid(" "));
+        assert!(invalid("a,,b"));
+        assert!(invalid("-logs-old,logs-*"));
+        assert!(invalid("logs/*"));
+        assert!(invalid("a, b"));
+        assert!(invalid(":logs"));
+        assert!(invalid("eu:west:logs"));
+        assert!(invalid("<logs>"));
+        assert!(invalid("_internal"));
+        assert!(IndexPattern::index_type("logs/*", "doc").multi_target().is_err());
+    }
+
+    #[test]
+    fn invalid_multi_targets() {
+        let invalid = |targets| IndexPattern::Targets(targets).multi_target().is_err();
+        assert!(invalid(vec![]));
+        assert!(invalid(vec![Target::exclude("logs-old"), Target::wildcard("logs-*")]));
+        assert!(invalid(vec![Target::index("logs-*")]));
+        assert!(invalid(vec![Target::wildcard("logs/*")]));
+        assert!(invalid(vec![Target::remote("", "logs")]));
+        assert!(invalid(vec![Target::remote("eu:west", "logs")]));
+        assert!(invalid(vec![Target::date_math("logs")]));
+        assert!(invalid(vec![Target::date_math("<logs-{now/d}>")]));
+    }
+}
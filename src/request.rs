@@ -0,0 +1,295 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::Method;
+use serde::Serialize;
+use std::{fmt, time::Duration};
+
+use crate::errors::EsError;
+
+/// Characters left unencoded in path segments besides alphanumerics, as in the official clients,
+/// so index patterns such as `logs-*,metrics` are sent as is.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b',')
+    .remove(b'*');
+
+/// Request sent by the EsClient to one of the nodes of the cluster.
+#[derive(Debug, Clone)]
+pub struct EsRequest {
+    method: Method,
+    path: String,
+    params: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    idempotent: bool,
+}
+
+impl EsRequest {
+    /// Create new EsRequest.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Http method of the request.
+    /// * `path` - Endpoint of the request relative to the node url, such as `test/_search`.
+    pub fn new(method: Method, path: &str) -> EsRequest {
+        // Only POST requests may change the cluster more than once if sent again.
+        let idempotent = method != Method::POST;
+        EsRequest {
+            method,
+            path: path.trim_start_matches('/').to_owned(),
+            params: Vec::new(),
+            body: None,
+            timeout: None,
+            idempotent,
+        }
+    }
+
+    /// Create new EsRequest to the path of the segments, such as the index and document id,
+    /// each percent-encoded so they can contain characters like `/`, `?` or `#`.
+    pub fn from_segments(method: Method, segments: &[&str]) -> EsRequest {
+        let path: Vec<String> = segments.iter().map(|segment| encode_segment(segment)).collect();
+        EsRequest::new(method, &path.join("/"))
+    }
+
+    /// Add a query parameter to the request.
+    pub fn param<V: ToString>(mut self, key: &str, value: V) -> EsRequest {
+        self.params.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Set the client side timeout of the request, overriding the timeout of the client.
+    pub fn timeout(mut self, timeout: Duration) -> EsRequest {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the json body of the request.
+    pub fn json<T: Serialize>(mut self, body: &T) -> Result<EsRequest, EsError> {
+        self.body = Some(serde_json::to_vec(body)?);
+        Ok(self)
+    }
+
+    /// Override whether the request can be sent again to another node, such as a search
+    /// sent with POST.
+    pub fn idempotent(mut self, idempotent: bool) -> EsRequest {
+        self.idempotent = idempotent;
+        self
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent
+    }
+}
+
+/// Percent-encode a path segment, such as an index name or document id.
+pub fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, SEGMENT).to_string()
+}
+
+/// Query string of the params, with keys and values percent-encoded.
+pub(crate) fn encode_query(params: &[(String, String)]) -> String {
+    let params: Vec<String> = params.iter()
+        .map(|(key, value)| format!("{}={}", encode_segment(key), encode_segment(value)))
+        .collect();
+    params.join("&")
+}
+
+/// When changes made by a request are made visible to search, sent as `refresh`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Refresh {
+    /// Refresh the affected shards right away.
+    True,
+    /// Leave the changes to the periodic refresh.
+    False,
+    /// Wait for the periodic refresh to make the changes visible before responding.
+    WaitFor,
+}
+
+impl fmt::Display for Refresh {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Refresh::True => write!(f, "true"),
+            Refresh::False => write!(f, "false"),
+            Refresh::WaitFor => write!(f, "wait_for"),
+        }
+    }
+}
+
+/// Number of shard copies that must be active before a write proceeds, sent as
+/// `wait_for_active_shards`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActiveShards {
+    /// Every copy of the shard.
+    All,
+    /// The number of copies, including the primary.
+    Count(u32),
+}
+
+impl fmt::Display for ActiveShards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActiveShards::All => write!(f, "all"),
+            ActiveShards::Count(count) => write!(f, "{}", count),
+        }
+    }
+}
+
+/// Format the duration as an Elasticsearch time value, such as `30s` or `1500ms`.
+pub(crate) fn time_value(duration: Duration) -> String {
+    match duration.as_millis() {
+        millis if millis % 1000 == 0 => format!("{}s", millis / 1000),
+        millis => format!("{}ms", millis),
+    }
+}
+
+/// Options every operation accepts, embedded in the options of each operation.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CommonOptions {
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) filter_path: Option<String>,
+    pub(crate) params: Vec<(String, String)>,
+}
+
+impl CommonOptions {
+    /// Set the options on the request, after the params of the operation itself.
+    pub(crate) fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(timeout) = self.request_timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(filter_path) = self.filter_path {
+            request = request.param("filter_path", filter_path);
+        }
+        for (key, value) in self.params {
+            request = request.param(&key, value);
+        }
+        request
+    }
+}
+
+/// Options of the operations writing to shards, embedded in the options of each of them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WriteOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) wait_for_active_shards: Option<ActiveShards>,
+}
+
+impl WriteOptions {
+    /// Set the options on the request.
+    pub(crate) fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(timeout) = self.timeout {
+            request = request.param("timeout", time_value(timeout));
+        }
+        if let Some(wait_for_active_shards) = self.wait_for_active_shards {
+            request = request.param("wait_for_active_shards", wait_for_active_shards);
+        }
+        request
+    }
+}
+
+/// Implement the setters of the `CommonOptions` every options type embeds as `common`.
+macro_rules! common_options {
+    ($options:ident) => {
+        impl $options {
+            /// Set the client side timeout of the request, overriding the timeout of the client.
+            pub fn request_timeout(mut self, timeout: std::time::Duration) -> $options {
+                self.common.request_timeout = Some(timeout);
+                self
+            }
+
+            /// Set the fields returned in the response, sent as `filter_path`. Filtering out fields
+            /// the typed response requires fails its deserialization.
+            pub fn filter_path(mut self, filter_path: &[&str]) -> $options {
+                self.common.filter_path = Some(filter_path.join(","));
+                self
+            }
+
+            /// Add a query parameter without a typed option.
+            pub fn param<V: ToString>(mut self, key: &str, value: V) -> $options {
+                self.common.params.push((key.to_owned(), value.to_string()));
+                self
+            }
+        }
+    };
+}
+
+pub(crate) use common_options;
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_query, encode_segment, time_value, ActiveShards, EsRequest, Refresh};
+    use reqwest::Method;
+    use std::time::Duration;
+
+    #[test]
+    fn format_time_value() {
+        assert_eq!(time_value(Duration::from_secs(30)), "30s");
+        assert_eq!(time_value(Duration::from_millis(1500)), "1500ms");
+        assert_eq!(time_value(Duration::from_millis(0)), "0s");
+    }
+
+    #[test]
+    fn encode_query_params() {
+        let params = vec![("q".to_owned(), "a b&c".to_owned()), ("filter_path".to_owned(), "took,hits".to_owned())];
+        assert_eq!(encode_query(&params), "q=a%20b%26c&filter_path=took,hits");
+    }
+
+    #[test]
+    fn encode_segments() {
+        assert_eq!(encode_segment("test"), "test");
+        assert_eq!(encode_segment("logs-*,metrics"), "logs-*,metrics");
+        assert_eq!(encode_segment("a/b"), "a%2Fb");
+        assert_eq!(encode_segment("what?#anchor"), "what%3F%23anchor");
+        assert_eq!(encode_segment("with space+plus"), "with%20space%2Bplus");
+        assert_eq!(encode_segment("100%"), "100%25");
+        assert_eq!(encode_segment("ünïcödé"), "%C3%BCn%C3%AFc%C3%B6d%C3%A9");
+        assert_eq!(encode_segment(".hidden"), ".hidden");
+    }
+
+    #[test]
+    fn request_from_segments() {
+        let request = EsRequest::from_segments(Method::PUT, &["test", "_doc", "https://example.com/a?b=c"]);
+        assert_eq!(request.path(), "test/_doc/https%3A%2F%2Fexample.com%2Fa%3Fb%3Dc");
+    }
+
+    #[test]
+    fn request_params() {
+        let request = EsRequest::new(Method::PUT, "/test")
+            .param("timeout", "30s")
+            .param("master_timeout", time_value(Duration::from_secs(60)));
+        assert_eq!(request.path(), "test");
+        assert_eq!(request.params(), &[
+            ("timeout".to_owned(), "30s".to_owned()),
+            ("master_timeout".to_owned(), "60s".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn format_params() {
+        assert_eq!(Refresh::WaitFor.to_string(), "wait_for");
+        assert_eq!(Refresh::True.to_string(), "true");
+        assert_eq!(ActiveShards::All.to_string(), "all");
+        assert_eq!(ActiveShards::Count(2).to_string(), "2");
+    }
+}
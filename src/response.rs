@@ -0,0 +1,164 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{
+    header::{HeaderMap, WARNING},
+    StatusCode,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::ops::Deref;
+
+use crate::{errors::EsError, pool::Node, transport::TransportResponse};
+
+/// Typed response of an API call, with the headers of the response and the node that answered.
+///
+/// Derefs to the body, so its fields can be read directly.
+#[derive(Debug, Clone)]
+pub struct Response<T> {
+    body: T,
+    status: StatusCode,
+    headers: HeaderMap,
+    node: Option<Node>,
+}
+
+impl<T> Response<T> {
+    pub(crate) fn new(body: T, res: TransportResponse) -> Response<T> {
+        let (status, headers, _, node) = res.into_parts();
+        Response { body, status, headers, node }
+    }
+
+    pub fn body(&self) -> &T {
+        &self.body
+    }
+
+    /// Take the body of the response.
+    pub fn into_body(self) -> T {
+        self.body
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Deprecation warnings sent by Elasticsearch in `Warning` headers, such as the use of
+    /// types on 7.x.
+    pub fn warnings(&self) -> Vec<String> {
+        parse_warnings(&self.headers)
+    }
+
+    /// Node that answered the request.
+    pub fn node(&self) -> Option<&Node> {
+        self.node.as_ref()
+    }
+}
+
+impl<T> Deref for Response<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.body
+    }
+}
+
+/// Successful response to a request sent with `EsClient::send`.
+#[derive(Debug, Clone)]
+pub struct EsResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    node: Option<Node>,
+}
+
+impl EsResponse {
+    pub(crate) fn from_response(res: TransportResponse) -> Result<EsResponse, EsError> {
+        let (status, headers, body, node) = res.into_parts();
+
+        match status.is_success() {
+            true => Ok(EsResponse { status, headers, body, node }),
+            false => Err(EsError::from_response(status, String::from_utf8_lossy(&body).into_owned())),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Deprecation warnings sent by Elasticsearch in `Warning` headers.
+    pub fn warnings(&self) -> Vec<String> {
+        parse_warnings(&self.headers)
+    }
+
+    /// Node that answered the request.
+    pub fn node(&self) -> Option<&Node> {
+        self.node.as_ref()
+    }
+
+    /// Raw body of the response.
+    pub fn bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Take the raw body of the response.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Deserialize the json body into `T`.
+    pub fn json<T>(&self) -> Result<T, EsError>
+        where for<'de> T: Deserialize<'de>
+    {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Deserialize the json body into a Value.
+    pub fn value(&self) -> Result<Value, EsError> {
+        self.json::<Value>()
+    }
+}
+
+/// Warning header value, with the message quoted after the code and agent.
+static WARNING_VALUE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\d{3} \S+ "((?:[^"\\]|\\.)*)""#).expect("Warning regex is valid"));
+
+/// Messages of the `Warning` headers, such as `299 Elasticsearch-7.10.0-51e9d6f "[types removal] ..."`.
+///
+/// Headers that do not follow the format are returned as is.
+pub(crate) fn parse_warnings(headers: &HeaderMap) -> Vec<String> {
+    headers.get_all(WARNING).iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(|value| match WARNING_VALUE.captures(value) {
+            Some(captures) => captures[1].replace("\\\"", "\"").replace("\\\\", "\\"),
+            None => value.to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_warnings;
+    use reqwest::header::{HeaderMap, HeaderValue, WARNING};
+
+    #[test]
+    fn parse_warning_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(WARNING, HeaderValue::from_static(
+            r#"299 Elasticsearch-7.10.0-51e9d6f "[types removal] Specifying types in search requests is deprecated." "Mon, 18 Oct 2021 10:00:00 GMT""#
+        ));
+        headers.append(WARNING, HeaderValue::from_static(r#"299 Elasticsearch-7.10.0-51e9d6f "quoted \"name\"""#));
+        headers.append(WARNING, HeaderValue::from_static("not a warning"));
+
+        assert_eq!(parse_warnings(&headers), vec![
+            "[types removal] Specifying types in search requests is deprecated.",
+            r#"quoted "name""#,
+            "not a warning",
+        ]);
+        assert!(parse_warnings(&HeaderMap::new()).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use reqwest::StatusCode;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::{request::EsRequest, transport::TransportError};
+
+/// Policy deciding which failed requests are sent again and how long to wait in between.
+///
+/// By default requests are retried up to 3 times on 429, 502, 503 and 504 responses and on
+/// connection errors or timeouts, waiting an exponential backoff with jitter. Requests that
+/// are not idempotent are only retried if enabled, unless they failed to connect and were
+/// never sent.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retry_on_status: Vec<StatusCode>,
+    retry_on_connection_error: bool,
+    retry_on_timeout: bool,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_on_status: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_on_connection_error: true,
+            retry_on_timeout: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create new RetryPolicy with the default settings.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Create RetryPolicy that never retries requests.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::default().max_retries(0)
+    }
+
+    /// Set the max number of times a request is sent again.
+    pub fn max_retries(mut self, max_retries: u32) -> RetryPolicy {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the backoff before the first retry, doubled for each following retry.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Set the max backoff between retries.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> RetryPolicy {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set whether the backoff is randomized, so clients do not retry all at once.
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the response statuses that are retried.
+    pub fn retry_on_status(mut self, statuses: &[StatusCode]) -> RetryPolicy {
+        self.retry_on_status = statuses.to_vec();
+        self
+    }
+
+    /// Set whether requests are retried when no node can be reached.
+    pub fn retry_on_connection_error(mut self, retry_on_connection_error: bool) -> RetryPolicy {
+        self.retry_on_connection_error = retry_on_connection_error;
+        self
+    }
+
+    /// Set whether requests are retried when they time out.
+    pub fn retry_on_timeout(mut self, retry_on_timeout: bool) -> RetryPolicy {
+        self.retry_on_timeout = retry_on_timeout;
+        self
+    }
+
+    /// Set whether requests that are not idempotent, such as indexing without an id, are retried.
+    pub fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> RetryPolicy {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    pub fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Whether the request is sent again after the response status.
+    pub fn retries_status(&self, request: &EsRequest, status: StatusCode) -> bool {
+        self.retries_request(request) && self.retry_on_status.contains(&status)
+    }
+
+    /// Whether the request is sent again after failing to get a response. A request that
+    /// failed to connect never reached the node, so it is retried whatever its method.
+    pub fn retries_error(&self, request: &EsRequest, error: &TransportError) -> bool {
+        match error {
+            error if error.is_connect() => self.retry_on_connection_error,
+            error if error.is_timeout() => self.retries_request(request) && self.retry_on_timeout,
+            _ => false,
+        }
+    }
+
+    /// Backoff before the retry, counting from 0 for the first retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self.initial_backoff
+            .checked_mul(2u32.saturating_pow(retry.min(16)))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        match self.jitter {
+            // Wait at least half of the backoff, and a random part of the other half.
+            true => {
+                let half = backoff / 2;
+                let random = RandomState::new().build_hasher().finish();
+                half + half.mul_f64((random % 1000) as f64 / 1000.0)
+            },
+            false => backoff,
+        }
+    }
+
+    fn retries_request(&self, request: &EsRequest) -> bool {
+        request.is_idempotent() || self.retry_non_idempotent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use crate::{
+        request::EsRequest,
+        transport::{TransportError, TransportErrorKind},
+    };
+    use reqwest::{Method, StatusCode};
+    use std::time::Duration;
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn backoff_with_jitter() {
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(100));
+        for _ in 0..20 {
+            let backoff = policy.backoff(1);
+            assert!(backoff >= Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn retries_status() {
+        let policy = RetryPolicy::new();
+        let get = EsRequest::new(Method::GET, "test/_doc/1");
+        let post = EsRequest::new(Method::POST, "test/_doc");
+
+        assert!(policy.retries_status(&get, StatusCode::TOO_MANY_REQUESTS));
+        assert!(policy.retries_status(&get, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.retries_status(&get, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!policy.retries_status(&post, StatusCode::SERVICE_UNAVAILABLE));
+
+        let policy = policy
+            .retry_on_status(&[StatusCode::INTERNAL_SERVER_ERROR])
+            .retry_non_idempotent(true);
+        assert!(policy.retries_status(&post, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!policy.retries_status(&post, StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn retries_error() {
+        let policy = RetryPolicy::new();
+        let get = EsRequest::new(Method::GET, "test/_doc/1");
+        let post = EsRequest::new(Method::POST, "test/_doc");
+        let connect = TransportError::new(TransportErrorKind::Connect, "connection refused");
+        let timeout = TransportError::new(TransportErrorKind::Timeout, "timed out");
+        let request = TransportError::new(TransportErrorKind::Request, "connection reset");
+
+        assert!(policy.retries_error(&get, &connect));
+        assert!(policy.retries_error(&post, &connect));
+        assert!(policy.retries_error(&get, &timeout));
+        assert!(!policy.retries_error(&post, &timeout));
+        assert!(!policy.retries_error(&get, &request));
+
+        let policy = policy.retry_on_connection_error(false);
+        assert!(!policy.retries_error(&post, &connect));
+    }
+}
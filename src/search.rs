@@ -1,11 +1,16 @@
-use reqwest;
 use reqwest::StatusCode;
-use serde_json::Value;
-use serde::Deserialize;
+use serde_json::{Map, Value};
+use serde::{Deserialize, Deserializer};
+use std::{fmt, time::Duration};
 
+#[cfg(feature = "blocking")]
+use crate::blocking;
 use crate::client::{EsClient, IndexPattern};
+use crate::request::{common_options, time_value, CommonOptions, EsRequest};
+use crate::response::Response;
 use crate::utils::serialize_response;
-use crate::errors::ESGenericFail;
+use crate::errors::EsError;
+use crate::trace::{self, op_span};
 
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct EsSearchResponse<T> {
@@ -14,6 +19,48 @@ pub struct EsSearchResponse<T> {
     #[serde(rename = "_shards")]
     shards: ShardResults,
     hits: HitResults<T>,
+    /// Id of the scroll context, returned when the search is sent with `scroll`.
+    #[serde(rename = "_scroll_id", default)]
+    pub scroll_id: Option<String>,
+}
+
+impl<T> EsSearchResponse<T> {
+    /// Milliseconds Elasticsearch took to run the search.
+    pub fn took(&self) -> u16 {
+        self.took
+    }
+
+    /// Whether a shard timed out, the hits are then partial.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Number of hits matching the query, a lower bound if not tracked exactly.
+    pub fn total(&self) -> u64 {
+        self.hits.total
+    }
+
+    /// Highest score of the hits, `None` if the hits are not scored.
+    pub fn max_score(&self) -> Option<f32> {
+        self.hits.max_score
+    }
+
+    /// Hits returned, in order.
+    pub fn hits(&self) -> &[Data<T>] {
+        &self.hits.hits
+    }
+
+    /// Take the sources of the hits, in order.
+    pub fn into_sources(self) -> Vec<T> {
+        self.hits.hits.into_iter().map(|hit| hit.source).collect()
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct EsCountResponse {
+    pub count: u64,
+    #[serde(rename = "_shards")]
+    shards: ShardResults,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -27,75 +74,388 @@ struct ShardResults {
 #[derive(Deserialize, Debug, PartialEq)]
 struct HitResults<T> {
     hits: Vec<Data<T>>,
-    total: u16,
+    #[serde(deserialize_with = "deserialize_total")]
+    total: u64,
     max_score: Option<f32>,
 }
 
+/// Total hits, sent as a number before 7.x and as an object since.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TotalHits {
+    Count(u64),
+    Object { value: u64 },
+}
+
+fn deserialize_total<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where D: Deserializer<'de>
+{
+    match TotalHits::deserialize(deserializer)? {
+        TotalHits::Count(total) => Ok(total),
+        TotalHits::Object { value } => Ok(value),
+    }
+}
+
+/// Hit of a search, with its source deserialized as `T`.
 #[derive(Deserialize, Debug, PartialEq)]
-struct Data<T> {
+pub struct Data<T> {
     #[serde(rename = "_source")]
     source: T,
     #[serde(rename = "_index")]
     index: String,
     #[serde(rename = "_type")]
-    doc_type: String,
+    doc_type: Option<String>,
     #[serde(rename = "_id")]
     id: String,
     #[serde(rename = "_score")]
-    score: f32,
+    score: Option<f32>,
+}
+
+impl<T> Data<T> {
+    /// Source of the document.
+    pub fn source(&self) -> &T {
+        &self.source
+    }
+
+    /// Index the document is stored in.
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    /// Type of the document, for clusters before 7.0.
+    pub fn doc_type(&self) -> Option<&str> {
+        self.doc_type.as_deref()
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Score of the hit, `None` if the hits are sorted on other fields.
+    pub fn score(&self) -> Option<f32> {
+        self.score
+    }
+}
+
+/// Options of the search request.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    routing: Option<String>,
+    preference: Option<String>,
+    timeout: Option<Duration>,
+    search_type: Option<SearchType>,
+    scroll: Option<Duration>,
+    common: CommonOptions,
+}
+
+common_options!(SearchOptions);
+
+impl SearchOptions {
+    /// Create new SearchOptions.
+    pub fn new() -> SearchOptions {
+        SearchOptions::default()
+    }
+
+    /// Set the value used to route the request to a shard, sent as `routing`.
+    pub fn routing(mut self, routing: &str) -> SearchOptions {
+        self.routing = Some(routing.to_owned());
+        self
+    }
+
+    /// Set the nodes or shards the search runs on, sent as `preference`.
+    pub fn preference(mut self, preference: &str) -> SearchOptions {
+        self.preference = Some(preference.to_owned());
+        self
+    }
+
+    /// Set how long each shard searches before returning the hits found so far, sent as `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> SearchOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set how the scores of the hits are computed, sent as `search_type`.
+    pub fn search_type(mut self, search_type: SearchType) -> SearchOptions {
+        self.search_type = Some(search_type);
+        self
+    }
+
+    /// Set how long the search context is kept for the next scroll request, sent as `scroll`.
+    pub fn scroll(mut self, scroll: Duration) -> SearchOptions {
+        self.scroll = Some(scroll);
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(routing) = self.routing {
+            request = request.param("routing", routing);
+        }
+        if let Some(preference) = self.preference {
+            request = request.param("preference", preference);
+        }
+        if let Some(timeout) = self.timeout {
+            request = request.param("timeout", time_value(timeout));
+        }
+        if let Some(search_type) = self.search_type {
+            request = request.param("search_type", search_type);
+        }
+        if let Some(scroll) = self.scroll {
+            request = request.param("scroll", time_value(scroll));
+        }
+        self.common.apply(request)
+    }
+}
+
+/// Options of the count request.
+#[derive(Debug, Clone, Default)]
+pub struct CountOptions {
+    routing: Option<String>,
+    preference: Option<String>,
+    common: CommonOptions,
 }
 
-pub async fn search_req<'a, T>(client: &EsClient, search_index: IndexPattern<'a>, query: Value) -> Result<EsSearchResponse<T>, Box<dyn std::error::Error>>
+common_options!(CountOptions);
+
+impl CountOptions {
+    /// Create new CountOptions.
+    pub fn new() -> CountOptions {
+        CountOptions::default()
+    }
+
+    /// Set the value used to route the request to a shard, sent as `routing`.
+    pub fn routing(mut self, routing: &str) -> CountOptions {
+        self.routing = Some(routing.to_owned());
+        self
+    }
+
+    /// Set the nodes or shards the count runs on, sent as `preference`.
+    pub fn preference(mut self, preference: &str) -> CountOptions {
+        self.preference = Some(preference.to_owned());
+        self
+    }
+
+    fn apply(self, mut request: EsRequest) -> EsRequest {
+        if let Some(routing) = self.routing {
+            request = request.param("routing", routing);
+        }
+        if let Some(preference) = self.preference {
+            request = request.param("preference", preference);
+        }
+        self.common.apply(request)
+    }
+}
+
+/// How the scores of the hits are computed, sent as `search_type`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchType {
+    /// Score the hits with the term frequencies of each shard.
+    QueryThenFetch,
+    /// Collect the term frequencies of every shard first, for more accurate scores.
+    DfsQueryThenFetch,
+}
+
+impl fmt::Display for SearchType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchType::QueryThenFetch => write!(f, "query_then_fetch"),
+            SearchType::DfsQueryThenFetch => write!(f, "dfs_query_then_fetch"),
+        }
+    }
+}
+
+/// Search request built step by step, created with `EsClient::search_request`.
+#[derive(Debug)]
+pub struct SearchRequest<'a, C = EsClient> {
+    client: &'a C,
+    search_on: IndexPattern<'a>,
+    body: Map<String, Value>,
+    options: SearchOptions,
+}
+
+impl<'a, C> SearchRequest<'a, C> {
+    pub(crate) fn new(client: &'a C, search_on: IndexPattern<'a>) -> SearchRequest<'a, C> {
+        SearchRequest { client, search_on, body: Map::new(), options: SearchOptions::default() }
+    }
+
+    /// Set the query clause, such as `{"match_all": {}}`, sent as `query` in the body.
+    pub fn query(mut self, query: Value) -> SearchRequest<'a, C> {
+        self.body.insert("query".to_owned(), query);
+        self
+    }
+
+    /// Set how many hits are returned, sent as `size` in the body.
+    pub fn size(mut self, size: u64) -> SearchRequest<'a, C> {
+        self.body.insert("size".to_owned(), size.into());
+        self
+    }
+
+    /// Set how many hits are skipped, sent as `from` in the body.
+    pub fn from(mut self, from: u64) -> SearchRequest<'a, C> {
+        self.body.insert("from".to_owned(), from.into());
+        self
+    }
+
+    /// Set the order of the hits, such as `[{"date": "desc"}]`, sent as `sort` in the body.
+    pub fn sort(mut self, sort: Value) -> SearchRequest<'a, C> {
+        self.body.insert("sort".to_owned(), sort);
+        self
+    }
+
+    /// Set the fields of the source returned with the hits, sent as `_source` in the body.
+    pub fn source(mut self, source: Value) -> SearchRequest<'a, C> {
+        self.body.insert("_source".to_owned(), source);
+        self
+    }
+
+    /// Set the aggregations run on the hits, sent as `aggs` in the body.
+    pub fn aggs(mut self, aggs: Value) -> SearchRequest<'a, C> {
+        self.body.insert("aggs".to_owned(), aggs);
+        self
+    }
+
+    /// Set the filter applied to the hits after the aggregations are computed, sent as
+    /// `post_filter` in the body.
+    pub fn post_filter(mut self, post_filter: Value) -> SearchRequest<'a, C> {
+        self.body.insert("post_filter".to_owned(), post_filter);
+        self
+    }
+
+    /// Set the fields highlighted in the hits, sent as `highlight` in the body.
+    pub fn highlight(mut self, highlight: Value) -> SearchRequest<'a, C> {
+        self.body.insert("highlight".to_owned(), highlight);
+        self
+    }
+
+    /// Set the sort values of the last hit of the previous page, sent as `search_after` in the
+    /// body.
+    pub fn search_after(mut self, search_after: Value) -> SearchRequest<'a, C> {
+        self.body.insert("search_after".to_owned(), search_after);
+        self
+    }
+
+    /// Set whether the total hits are counted exactly, `true`, `false` or the number of hits
+    /// counted exactly, sent as `track_total_hits` in the body.
+    pub fn track_total_hits<V: Into<Value>>(mut self, track_total_hits: V) -> SearchRequest<'a, C> {
+        self.body.insert("track_total_hits".to_owned(), track_total_hits.into());
+        self
+    }
+
+    /// Set a field of the body without a typed method, such as `min_score`.
+    pub fn body_field(mut self, key: &str, value: Value) -> SearchRequest<'a, C> {
+        self.body.insert(key.to_owned(), value);
+        self
+    }
+
+    /// Set the options of the request, such as `routing`, `search_type` and `scroll`.
+    pub fn options(mut self, options: SearchOptions) -> SearchRequest<'a, C> {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a> SearchRequest<'a> {
+    /// Send the search, deserializing the source of the hits as `T`.
+    pub async fn send<T>(self) -> Result<Response<EsSearchResponse<T>>, EsError>
+        where for<'de> T: Deserialize<'de>
+    {
+        search_req(self.client, self.search_on, Value::Object(self.body), self.options).await
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<'a> SearchRequest<'a, blocking::EsClient> {
+    /// Send the search, blocking until it completes, deserializing the source of the hits as `T`.
+    pub fn send<T>(self) -> Result<Response<EsSearchResponse<T>>, EsError>
+        where for<'de> T: Deserialize<'de>
+    {
+        self.client.block_on(search_req(self.client.get_async(), self.search_on, Value::Object(self.body), self.options))
+    }
+}
+
+pub async fn search_req<'a, T>(client: &EsClient, search_index: IndexPattern<'a>, query: Value, options: SearchOptions) -> Result<Response<EsSearchResponse<T>>, EsError>
     where for<'de> T: Deserialize<'de>
 {
-    let (index, doc_type) = match search_index {
-        IndexPattern::Index(index) => (index, None),
-        IndexPattern::IndexType(index, doc_type) => (index, Some(doc_type))
-    };
+    op_span!("search").run(client, async move {
+        client.detect_version().await?;
 
-    let res = client.post(index, doc_type, Some("_search"))
-        .json(&query)
-        .send()
-        .await?;
+        let (index, doc_type) = search_index.multi_target()?;
+        trace::record_index(&index);
 
-    let res = match res.status() {
-        StatusCode::OK => {
-            let text = res.text().await?;
-            let data = serialize_response::<EsSearchResponse<T>>(&text)?;
-            data
-        },
-        StatusCode::BAD_REQUEST => { 
-            let text = res.text().await?;
-            let data = serialize_response::<ESGenericFail>(&text)?;
-            return Err(Box::new(data));
-        },
-        StatusCode::NOT_FOUND => {
-            let text = res.text().await?;
-            let data = serialize_response::<ESGenericFail>(&text)?;
-            return Err(Box::new(data));
+        client.check_doc_type(doc_type)?;
+
+        // Searches only read from the cluster, so they are safe to send again to another node.
+        let request = client.post_request(&index, doc_type, Some("_search"))
+            .idempotent(true)
+            .json(&query)?;
+        let res = client.send_request(options.apply(request)).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => {
+                let body = serialize_response::<EsSearchResponse<T>>(&text)?;
+                trace::record_took(body.took.into());
+                Ok(Response::new(body, res))
+            },
+            _ => Err(EsError::from_response(status, text)),
         }
-        _ => panic!("Request failed in an unexpected way..."),
-    };
-    Ok(res)
+    }).await
+}
+
+pub async fn count_req<'a>(client: &EsClient, count_on: IndexPattern<'a>, query: Value, options: CountOptions) -> Result<Response<EsCountResponse>, EsError> {
+    op_span!("count").run(client, async move {
+        client.detect_version().await?;
+
+        let (index, doc_type) = count_on.multi_target()?;
+        trace::record_index(&index);
+
+        client.check_doc_type(doc_type)?;
+
+        let request = client.post_request(&index, doc_type, Some("_count"))
+            .idempotent(true)
+            .json(&query)?;
+        let res = client.send_request(options.apply(request)).await?;
+
+        let status = res.status();
+        let text = res.text();
+
+        match status {
+            StatusCode::OK => Ok(Response::new(serialize_response::<EsCountResponse>(&text)?, res)),
+            _ => Err(EsError::from_response(status, text)),
+        }
+    }).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
+        count_req,
         search_req,
         EsSearchResponse,
         ShardResults,
         HitResults,
         Data,
+        CountOptions,
+        SearchOptions,
+        SearchType,
     };
-    use crate::client::{
-        EsClient,
-        IndexPattern,
+    use crate::{
+        client::{
+            EsClient,
+            EsClientBuilder,
+            IndexPattern,
+            Target,
+            Version,
+        },
+        transport::{InMemoryTransport, TransportResponse},
     };
 
-    use mockito::mock;
+    use mockito::{mock, Matcher};
+    use reqwest::StatusCode;
+    use std::{sync::Arc, time::Duration};
     use tokio::runtime::Runtime;
-    use serde_json::json;
+    use serde_json::{json, Value};
     use serde::Deserialize;
 
     #[derive(Deserialize, Debug, PartialEq)]
@@ -161,12 +521,13 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = search_req::<Results>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             json!({
                 "query": {
                     "match_all": {}
                 }
-            })
+            }),
+            SearchOptions::default(),
         );
         
         let res = rt.block_on(res);
@@ -184,8 +545,8 @@ mod tests {
                         Data {
                             id: "4jjieidk".to_owned(),
                             index: "test".to_owned(),
-                            doc_type: "_doc".to_owned(),
-                            score: 1.0,
+                            doc_type: Some("_doc".to_owned()),
+                            score: Some(1.0),
                             source: Results {
                                 a: "test".to_owned(),
                                 b: 1
@@ -195,8 +556,9 @@ mod tests {
                     total: 1,
                     max_score: Some(1.0),
                 },
+                scroll_id: None,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -247,12 +609,13 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = search_req::<Results>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             json!({
                 "query": {
                     "match_all": {}
                 }
-            })
+            }),
+            SearchOptions::default(),
         );
         
         let res = rt.block_on(res);
@@ -270,8 +633,9 @@ mod tests {
                     total: 0,
                     max_score: None,
                 },
+                scroll_id: None,
         };
-        assert_eq!(res.unwrap(), expected_res);
+        assert_eq!(res.unwrap().into_body(), expected_res);
     }
 
     #[test]
@@ -315,20 +679,20 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = search_req::<Results>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
             json!({
                 "query": {
                     "match_all": {}
                 }
-            })
+            }),
+            SearchOptions::default(),
         );
         
         let res = rt.block_on(res);
-        assert_eq!(res.is_err(), true);
+        assert!(res.is_err());
     }
 
     #[test]
-    #[should_panic]
     fn unexpected_error_es6() {
         let rt = Runtime::new().unwrap();
         let _client_mock = mock("GET", "/")
@@ -370,13 +734,299 @@ mod tests {
         let client = EsClient::new("http://127.0.0.1", 1234);
         let res = search_req::<Results>(
             &client,
-            IndexPattern::Index("test"),
+            IndexPattern::index("test"),
+            json!({
+                "query": {
+                    "match_all": {}
+                }
+            }),
+            SearchOptions::default(),
+        );
+        let res = rt.block_on(res);
+        assert_eq!(res.unwrap_err().status(), Some(500));
+    }
+
+    #[test]
+    fn successful_search_es7_with_total_object() {
+        let rt = Runtime::new().unwrap();
+        let _search_mock = mock("POST", "/test/_search")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "took": 1,
+                "timed_out": false,
+                "_shards": {
+                    "total": 1,
+                    "successful": 1,
+                    "skipped": 0,
+                    "failed": 0
+                },
+                "hits": {
+                    "hits": [{
+                        "_id": "4jjieidk",
+                        "_index": "test",
+                        "_score": null,
+                        "_source": {
+                            "a": "test",
+                            "b": 1
+                        }
+                    }],
+                    "total": {
+                        "value": 1,
+                        "relation": "eq"
+                    },
+                    "max_score": null
+                }
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let res = search_req::<Results>(
+            &client,
+            IndexPattern::index("test"),
+            json!({
+                "query": {
+                    "match_all": {}
+                }
+            }),
+            SearchOptions::default(),
+        );
+
+        let res = rt.block_on(res).unwrap();
+        assert_eq!(res.hits.total, 1);
+        assert_eq!(res.hits.hits[0].doc_type, None);
+        assert_eq!(res.hits.hits[0].score, None);
+    }
+
+    #[test]
+    fn failed_search_with_type_es8() {
+        let rt = Runtime::new().unwrap();
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(8, 11, 3))
+            .build()
+            .unwrap();
+        let res = search_req::<Results>(
+            &client,
+            IndexPattern::index_type("test", "doc"),
+            json!({
+                "query": {
+                    "match_all": {}
+                }
+            }),
+            SearchOptions::default(),
+        );
+
+        let res = rt.block_on(res);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn successful_search_with_options_es7() {
+        let rt = Runtime::new().unwrap();
+        let search_mock = mock("POST", "/params/_search")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("routing".into(), "u1".into()),
+                Matcher::UrlEncoded("preference".into(), "_local".into()),
+                Matcher::UrlEncoded("filter_path".into(), "took,timed_out,_shards,hits".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "took": 1,
+                "timed_out": false,
+                "_shards": {
+                    "total": 1,
+                    "successful": 1,
+                    "skipped": 0,
+                    "failed": 0
+                },
+                "hits": {
+                    "hits": [],
+                    "total": {
+                        "value": 0,
+                        "relation": "eq"
+                    },
+                    "max_score": null
+                }
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let options = SearchOptions::new()
+            .routing("u1")
+            .preference("_local")
+            .filter_path(&["took", "timed_out", "_shards", "hits"]);
+        let res = search_req::<Results>(
+            &client,
+            IndexPattern::index("params"),
             json!({
                 "query": {
                     "match_all": {}
                 }
-            })
+            }),
+            options,
         );
-        let _ = rt.block_on(res);
+
+        assert_eq!(rt.block_on(res).unwrap().hits.total, 0);
+        search_mock.assert();
+    }
+
+    #[test]
+    fn successful_count_multi_target_es7() {
+        let rt = Runtime::new().unwrap();
+        let count_mock = mock("POST", "/logs-*,-logs-old,eu%3Alogs-*,%3Clogs-%7Bnow%2Fd%7D%3E/_count")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("routing".into(), "u1".into()),
+                Matcher::UrlEncoded("terminate_after".into(), "100".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "count": 42,
+                "_shards": {
+                    "total": 3,
+                    "successful": 3,
+                    "skipped": 0,
+                    "failed": 0
+                }
+            }"#)
+            .create();
+
+        let client = EsClient::builder()
+            .host("http://127.0.0.1")
+            .port(1234)
+            .version(Version::new(7, 10, 2))
+            .build()
+            .unwrap();
+        let res = count_req(
+            &client,
+            IndexPattern::Targets(vec![
+                Target::wildcard("logs-*"),
+                Target::exclude("logs-old"),
+                Target::remote("eu", "logs-*"),
+                Target::date_math("logs-{now/d}"),
+            ]),
+            json!({
+                "query": {
+                    "match_all": {}
+                }
+            }),
+            CountOptions::new().routing("u1").param("terminate_after", 100),
+        );
+
+        assert_eq!(rt.block_on(res).unwrap().count, 42);
+        count_mock.assert();
+    }
+
+    #[test]
+    fn search_with_builder() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, r#"{
+            "took": 2,
+            "timed_out": false,
+            "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0},
+            "hits": {
+                "total": {"value": 1, "relation": "eq"},
+                "max_score": null,
+                "hits": [{"_index": "test", "_id": "1", "_score": null, "_source": {"a": "test", "b": 5}}]
+            }
+        }"#))));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let res = client.search_request("test")
+            .query(json!({"match": {"a": "test"}}))
+            .size(10)
+            .sort(json!([{"b": "desc"}]))
+            .options(SearchOptions::new().routing("u1"))
+            .send::<Results>();
+        let res = rt.block_on(res).unwrap();
+        assert_eq!((res.took(), res.timed_out(), res.total(), res.max_score()), (2, false, 1, None));
+        assert_eq!((res.hits()[0].index(), res.hits()[0].id(), res.hits()[0].score()), ("test", "1", None));
+        assert_eq!(res.into_body().into_sources(), vec![Results { a: "test".to_owned(), b: 5 }]);
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url(), "http://localhost:9200/test/_search?routing=u1");
+        let body: Value = serde_json::from_slice(requests[0].body().unwrap()).unwrap();
+        assert_eq!(body, json!({"query": {"match": {"a": "test"}}, "size": 10, "sort": [{"b": "desc"}]}));
+    }
+
+    #[test]
+    fn search_with_builder_pagination() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, r#"{
+            "_scroll_id": "scroll-1",
+            "took": 1,
+            "timed_out": false,
+            "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0},
+            "hits": {"total": {"value": 0, "relation": "eq"}, "max_score": null, "hits": []}
+        }"#))));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let res = client.search_request("test")
+            .post_filter(json!({"term": {"b": 5}}))
+            .highlight(json!({"fields": {"a": {}}}))
+            .search_after(json!([5]))
+            .track_total_hits(1000)
+            .options(SearchOptions::new().search_type(SearchType::DfsQueryThenFetch).scroll(Duration::from_secs(60)))
+            .send::<Results>();
+        assert_eq!(rt.block_on(res).unwrap().scroll_id, Some("scroll-1".to_owned()));
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url(), "http://localhost:9200/test/_search?search_type=dfs_query_then_fetch&scroll=60s");
+        let body: Value = serde_json::from_slice(requests[0].body().unwrap()).unwrap();
+        assert_eq!(body, json!({
+            "post_filter": {"term": {"b": 5}},
+            "highlight": {"fields": {"a": {}}},
+            "search_after": [5],
+            "track_total_hits": 1000,
+        }));
+    }
+
+    #[test]
+    fn search_index_expressions() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, r#"{
+            "took": 1,
+            "timed_out": false,
+            "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0},
+            "hits": {"total": {"value": 0, "relation": "eq"}, "max_score": null, "hits": []}
+        }"#))));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        for index in &["logs-*", "a,b", "_all"] {
+            let res = search_req::<Results>(&client, IndexPattern::index(*index), json!({}), SearchOptions::default());
+            rt.block_on(res).unwrap();
+        }
+
+        let urls: Vec<String> = transport.requests().iter().map(|request| request.url().to_owned()).collect();
+        assert_eq!(urls, vec![
+            "http://localhost:9200/logs-*/_search",
+            "http://localhost:9200/a,b/_search",
+            "http://localhost:9200/_all/_search",
+        ]);
     }
 }
@@ -0,0 +1,164 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::pool::Node;
+
+/// Nodes of the cluster returned by the `_nodes/_all/http` request.
+#[derive(Deserialize, Debug)]
+pub struct EsNodesHttp {
+    pub nodes: HashMap<String, EsNodeInfo>,
+}
+
+/// Node of the cluster returned by the `_nodes/_all/http` request.
+#[derive(Deserialize, Debug)]
+pub struct EsNodeInfo {
+    pub name: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    pub http: Option<EsNodeHttp>,
+}
+
+/// Http details of a node, missing if http is disabled on the node.
+#[derive(Deserialize, Debug)]
+pub struct EsNodeHttp {
+    pub publish_address: String,
+}
+
+impl EsNodeInfo {
+    /// Whether the node only has the master role, and so should not receive requests.
+    pub fn is_master_only(&self) -> bool {
+        self.roles.iter().any(|role| role == "master")
+            && self.roles.iter().all(|role| role == "master" || role == "voting_only")
+    }
+}
+
+impl EsNodesHttp {
+    /// Nodes that pass the filter, with the scheme of the nodes the client was configured with.
+    pub fn into_nodes(self, scheme: &str, filter: fn(&EsNodeInfo) -> bool) -> Vec<Node> {
+        let mut nodes: Vec<Node> = self.nodes.values()
+            .filter(|info| filter(info))
+            .filter_map(|info| info.http.as_ref())
+            .filter_map(|http| parse_publish_address(scheme, &http.publish_address))
+            .collect();
+        // Keep the order stable between sniffs so round-robin is not reset.
+        nodes.sort_by_key(Node::url);
+        nodes
+    }
+}
+
+/// Parse a publish address such as `10.0.0.1:9200` or `es-1.internal/10.0.0.1:9200`,
+/// preferring the host name when it is sent.
+fn parse_publish_address(scheme: &str, publish_address: &str) -> Option<Node> {
+    let (host, address) = match publish_address.split_once('/') {
+        Some((host, address)) => (Some(host), address),
+        None => (None, publish_address),
+    };
+    let (ip, port) = address.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok()?;
+
+    Some(Node::new(scheme, host.unwrap_or(ip), port))
+}
+
+/// Default sniff filter, excluding master only nodes.
+pub fn exclude_master_only(info: &EsNodeInfo) -> bool {
+    !info.is_master_only()
+}
+
+/// Schedule of when the client refreshes its nodes from the cluster.
+#[derive(Debug)]
+pub(crate) struct Sniffer {
+    interval: Option<Duration>,
+    on_connection_failure: bool,
+    filter: fn(&EsNodeInfo) -> bool,
+    last_sniff: Mutex<Option<Instant>>,
+}
+
+impl Sniffer {
+    pub(crate) fn new(on_start: bool, interval: Option<Duration>, on_connection_failure: bool, filter: fn(&EsNodeInfo) -> bool) -> Sniffer {
+        // Without sniffing on start, the first periodic sniff is an interval after creation.
+        let last_sniff = match on_start {
+            true => None,
+            false => Some(Instant::now()),
+        };
+
+        Sniffer {
+            interval,
+            on_connection_failure,
+            filter,
+            last_sniff: Mutex::new(last_sniff),
+        }
+    }
+
+    pub(crate) fn filter(&self) -> fn(&EsNodeInfo) -> bool {
+        self.filter
+    }
+
+    pub(crate) fn on_connection_failure(&self) -> bool {
+        self.on_connection_failure
+    }
+
+    /// Whether a sniff is due, claiming it so concurrent requests do not sniff as well.
+    pub(crate) fn claim(&self) -> bool {
+        let mut last_sniff = self.last_sniff.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let is_due = match (*last_sniff, self.interval) {
+            (None, _) => true,
+            (Some(last_sniff), Some(interval)) => last_sniff.elapsed() >= interval,
+            (Some(_), None) => false,
+        };
+
+        if is_due {
+            *last_sniff = Some(Instant::now());
+        }
+        is_due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exclude_master_only, parse_publish_address, EsNodesHttp, Sniffer};
+    use crate::pool::Node;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn parse_publish_addresses() {
+        assert_eq!(parse_publish_address("http", "10.0.0.1:9200"), Some(Node::new("http", "10.0.0.1", 9200)));
+        assert_eq!(parse_publish_address("https", "es-1.internal/10.0.0.1:9201"), Some(Node::new("https", "es-1.internal", 9201)));
+        assert_eq!(parse_publish_address("http", "[::1]:9200"), Some(Node::new("http", "[::1]", 9200)));
+        assert_eq!(parse_publish_address("http", "10.0.0.1"), None);
+    }
+
+    #[test]
+    fn nodes_without_master_only() {
+        let nodes: EsNodesHttp = serde_json::from_str(r#"{
+            "_nodes": { "total": 3, "successful": 3, "failed": 0 },
+            "cluster_name": "docker-cluster",
+            "nodes": {
+                "a": { "name": "es-1", "roles": ["master"], "http": { "publish_address": "10.0.0.1:9200" } },
+                "b": { "name": "es-2", "roles": ["data", "ingest", "master"], "http": { "publish_address": "10.0.0.2:9200" } },
+                "c": { "name": "es-3", "roles": ["data"], "http": { "publish_address": "10.0.0.3:9200" } },
+                "d": { "name": "es-4", "roles": ["data"] }
+            }
+        }"#).unwrap();
+
+        assert_eq!(nodes.into_nodes("http", exclude_master_only), vec![
+            Node::new("http", "10.0.0.2", 9200),
+            Node::new("http", "10.0.0.3", 9200),
+        ]);
+    }
+
+    #[test]
+    fn sniff_schedule() {
+        let sniffer = Sniffer::new(true, Some(Duration::from_millis(20)), false, exclude_master_only);
+        assert!(sniffer.claim());
+        assert!(!sniffer.claim());
+        thread::sleep(Duration::from_millis(40));
+        assert!(sniffer.claim());
+
+        let sniffer = Sniffer::new(false, None, false, exclude_master_only);
+        assert!(!sniffer.claim());
+    }
+}
@@ -0,0 +1,280 @@
+//! Spans of the API calls with the `tracing` feature, named after the operation and following
+//! the OpenTelemetry database semantic conventions so Elasticsearch shows up as a client call
+//! to the `elasticsearch` database system.
+//!
+//! The same records feed the metrics hook of the client, spans are left out without the feature.
+
+use std::{future::Future, time::Instant};
+
+use crate::{
+    client::EsClient,
+    errors::EsError,
+    metrics,
+    pool::Node,
+    transport::{TransportRequest, TransportResponse},
+};
+
+/// Span of an API call, entered while its requests are sent.
+pub(crate) struct OpSpan {
+    pub(crate) operation: &'static str,
+    #[cfg(feature = "tracing")]
+    pub(crate) span: tracing::Span,
+}
+
+/// Create the OpSpan of the operation, such as `op_span!("search")`.
+macro_rules! op_span {
+    ($operation:literal) => {{
+        #[cfg(feature = "tracing")]
+        let span = $crate::trace::OpSpan {
+            operation: $operation,
+            span: tracing::info_span!(
+                $operation,
+                otel.name = $operation,
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+                db.system = "elasticsearch",
+                db.operation = $operation,
+                db.elasticsearch.path_parts.index = tracing::field::Empty,
+                http.request.method = tracing::field::Empty,
+                http.request.resend_count = tracing::field::Empty,
+                http.response.status_code = tracing::field::Empty,
+                url.full = tracing::field::Empty,
+                server.address = tracing::field::Empty,
+                server.port = tracing::field::Empty,
+                "error.type" = tracing::field::Empty,
+                elasticsearch.took = tracing::field::Empty,
+                duration_ms = tracing::field::Empty,
+            ),
+        };
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::trace::OpSpan { operation: $operation };
+        span
+    }};
+}
+
+pub(crate) use op_span;
+
+impl OpSpan {
+    /// Run the operation in the span, recording its duration and error, and the metrics
+    /// of the operation with the metrics hook of the client.
+    pub(crate) async fn run<T, F>(self, client: &EsClient, operation: F) -> Result<T, EsError>
+        where F: Future<Output = Result<T, EsError>>
+    {
+        let start = Instant::now();
+        #[cfg(feature = "tracing")]
+        let operation = tracing::Instrument::instrument(operation, self.span.clone());
+        let (res, stats) = metrics::collect(operation).await;
+        let duration = start.elapsed();
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("duration_ms", duration.as_millis() as u64);
+            if let Err(error) = &res {
+                self.span.record("otel.status_code", "ERROR");
+                self.span.record("error.type", error_type(error).as_str());
+            }
+        }
+
+        client.metrics().record(self.operation, &stats, duration, res.as_ref().err());
+        res
+    }
+}
+
+/// Record the targets of the operation.
+pub(crate) fn record_index(index: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("db.elasticsearch.path_parts.index", index);
+    metrics::update(|stats| stats.index = Some(index.to_owned()));
+}
+
+/// Record the node and request of an attempt, the last attempt overriding earlier ones.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_request(node: &Node, request: &TransportRequest) {
+    let sent = request.body().map_or(0, <[u8]>::len) as u64;
+    metrics::update(|stats| stats.bytes_sent += sent);
+
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("http.request.method", request.method().as_str());
+        span.record("url.full", request.url());
+        span.record("server.address", node.host());
+        span.record("server.port", node.port());
+    }
+}
+
+/// Record the status and size of the response to an attempt.
+pub(crate) fn record_response(res: &TransportResponse) {
+    let status = res.status().as_u16();
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("http.response.status_code", status);
+    metrics::update(|stats| {
+        stats.status = Some(status);
+        stats.bytes_received += res.body().len() as u64;
+    });
+}
+
+/// Record how many times the request was sent again.
+pub(crate) fn record_retries(retries: u32) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("http.request.resend_count", retries);
+    metrics::update(|stats| stats.retries = retries);
+}
+
+/// Record the time Elasticsearch took to run the operation, in milliseconds.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_took(took: u64) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("elasticsearch.took", took);
+}
+
+/// Low cardinality type of the error, the type reported by Elasticsearch if it responded with one.
+#[cfg(feature = "tracing")]
+fn error_type(error: &EsError) -> String {
+    match error {
+        EsError::Api { error_type, .. } => error_type.clone(),
+        EsError::Http { status, .. } => status.to_string(),
+        EsError::Transport(_) => "transport".to_owned(),
+        EsError::Timeout(_) => "timeout".to_owned(),
+        EsError::Deserialize(_) => "deserialize".to_owned(),
+        EsError::MissingId => "missing_id".to_owned(),
+        EsError::VersionUnsupported(_) => "version_unsupported".to_owned(),
+        EsError::InvalidTarget(_) => "invalid_target".to_owned(),
+        EsError::Deprecated(_) => "deprecated".to_owned(),
+        EsError::Rejected(_) => "rejected".to_owned(),
+        EsError::Config(_) => "config".to_owned(),
+        EsError::Io(_) => "io".to_owned(),
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{
+        client::{EsClientBuilder, Version},
+        transport::{InMemoryTransport, TransportResponse},
+    };
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        sync::{Arc, Mutex, MutexGuard},
+    };
+    use tokio::runtime::Runtime;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+    use tracing_core::span::Current;
+
+    /// Fields recorded on each span by name.
+    type SpanFields = HashMap<String, HashMap<String, String>>;
+    type Spans = Arc<Mutex<SpanFields>>;
+
+    /// Subscriber recording the fields of the spans, entered on the current thread.
+    #[derive(Default)]
+    struct Recorder {
+        spans: Spans,
+        metadata: Mutex<Vec<&'static Metadata<'static>>>,
+        stack: Mutex<Vec<u64>>,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name().to_owned(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+    }
+
+    impl Recorder {
+        fn fields(&self, id: &Id) -> (String, MutexGuard<'_, SpanFields>) {
+            let name = self.metadata.lock().unwrap()[id.into_u64() as usize - 1].name().to_owned();
+            (name, self.spans.lock().unwrap())
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut metadata = self.metadata.lock().unwrap();
+            metadata.push(span.metadata());
+            let id = Id::from_u64(metadata.len() as u64);
+            drop(metadata);
+
+            let (name, mut spans) = self.fields(&id);
+            span.record(&mut Fields(spans.entry(name).or_default()));
+            id
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let (name, mut spans) = self.fields(span);
+            values.record(&mut Fields(spans.entry(name).or_default()));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            match self.stack.lock().unwrap().last() {
+                Some(id) => Current::new(Id::from_u64(*id), self.metadata.lock().unwrap()[*id as usize - 1]),
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[test]
+    fn record_search_span() {
+        let rt = Runtime::new().unwrap();
+        let transport = InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, r#"{
+            "took": 7,
+            "timed_out": false,
+            "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0},
+            "hits": {"total": {"value": 0, "relation": "eq"}, "max_score": null, "hits": []}
+        }"#)));
+        let client = EsClientBuilder::new()
+            .url("http://es-1:9200")
+            .version(Version::new(7, 10, 0))
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let recorder = Recorder::default();
+        let spans = recorder.spans.clone();
+        tracing::subscriber::with_default(recorder, || {
+            rt.block_on(client.search_request("test").query(json!({"match_all": {}})).send::<Value>()).unwrap();
+        });
+
+        let spans = spans.lock().unwrap();
+        let span = &spans["search"];
+        assert_eq!(span["otel.kind"], "client");
+        assert_eq!(span["db.system"], "elasticsearch");
+        assert_eq!(span["db.operation"], "search");
+        assert_eq!(span["db.elasticsearch.path_parts.index"], "test");
+        assert_eq!(span["http.request.method"], "POST");
+        assert_eq!(span["http.response.status_code"], "200");
+        assert_eq!(span["url.full"], "http://es-1:9200/test/_search");
+        assert_eq!(span["server.address"], "es-1");
+        assert_eq!(span["server.port"], "9200");
+        assert_eq!(span["elasticsearch.took"], "7");
+        assert!(span.contains_key("duration_ms"));
+        assert!(!span.contains_key("error.type"));
+    }
+}
@@ -0,0 +1,383 @@
+use futures::future::BoxFuture;
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use std::{
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::pool::Node;
+
+/// HTTP layer the EsClient sends its requests with, `ReqwestTransport` unless another
+/// transport is set on the EsClientBuilder.
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send the request to the node in its url and read the whole response.
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        (**self).send(request)
+    }
+}
+
+/// Request ready to be sent to a node, with the authorization and default headers set.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub(crate) method: Method,
+    pub(crate) url: String,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Option<Vec<u8>>,
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl TransportRequest {
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Full url of the request, including the node and the query string.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Headers of the request, which middleware can change before it is sent.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// Time to wait for the response before failing with `TransportErrorKind::Timeout`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+/// Response read by a transport, returned whatever its status.
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    pub(crate) node: Option<Node>,
+}
+
+impl TransportResponse {
+    /// Create new TransportResponse without headers.
+    pub fn new<B: Into<Vec<u8>>>(status: StatusCode, body: B) -> TransportResponse {
+        TransportResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+            node: None,
+        }
+    }
+
+    /// Set the headers of the response.
+    pub fn headers(mut self, headers: HeaderMap) -> TransportResponse {
+        self.headers = headers;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn get_headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Body of the response as text, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Node that answered the request, set by the client once the transport returns.
+    pub fn node(&self) -> Option<&Node> {
+        self.node.as_ref()
+    }
+
+    pub(crate) fn into_parts(self) -> (StatusCode, HeaderMap, Vec<u8>, Option<Node>) {
+        (self.status, self.headers, self.body, self.node)
+    }
+}
+
+/// How a request failed, deciding whether it is sent to another node or retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransportErrorKind {
+    /// Node could not be reached, the request was not sent.
+    Connect,
+    /// No response was received in time.
+    Timeout,
+    /// Connection failed while sending the request or reading the response.
+    Request,
+    /// Request could not be built or any other failure.
+    Other,
+}
+
+/// Error of a transport failing to get a response.
+#[derive(Debug)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    error: Box<dyn Error + Send + Sync>,
+}
+
+impl TransportError {
+    /// Create new TransportError.
+    pub fn new<E: Into<Box<dyn Error + Send + Sync>>>(kind: TransportErrorKind, error: E) -> TransportError {
+        TransportError {
+            kind,
+            error: error.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    pub fn is_connect(&self) -> bool {
+        self.kind == TransportErrorKind::Connect
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.kind == TransportErrorKind::Timeout
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for TransportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(error: reqwest::Error) -> TransportError {
+        let kind = match &error {
+            // A connect timeout is a connect error, the request was not sent.
+            error if error.is_connect() => TransportErrorKind::Connect,
+            error if error.is_timeout() => TransportErrorKind::Timeout,
+            error if error.is_request() || error.is_body() => TransportErrorKind::Request,
+            _ => TransportErrorKind::Other,
+        };
+        TransportError::new(kind, error)
+    }
+}
+
+/// Transport sending requests with a reqwest client, configured by the EsClientBuilder.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Create new ReqwestTransport sending requests with the client.
+    pub fn new(client: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let res = request_builder(&self.client, request).send().await?;
+            let status = res.status();
+            let headers = res.headers().clone();
+            let body = res.bytes().await?.to_vec();
+            Ok(TransportResponse { status, headers, body, node: None })
+        })
+    }
+}
+
+/// Build the reqwest request for the transport request.
+pub(crate) fn request_builder(client: &reqwest::Client, request: TransportRequest) -> reqwest::RequestBuilder {
+    let mut builder = client
+        .request(request.method, &request.url)
+        .headers(request.headers);
+
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    match request.body {
+        Some(body) => builder.body(body),
+        None => builder,
+    }
+}
+
+type Handler = dyn Fn(&TransportRequest) -> Result<TransportResponse, TransportError> + Send + Sync;
+
+/// Transport answering requests with a handler instead of a node, recording the requests
+/// so tests can check what the client sent.
+pub struct InMemoryTransport {
+    handler: Box<Handler>,
+    requests: Mutex<Vec<TransportRequest>>,
+}
+
+impl InMemoryTransport {
+    /// Create new InMemoryTransport answering every request with the handler.
+    pub fn new<F>(handler: F) -> InMemoryTransport
+        where F: Fn(&TransportRequest) -> Result<TransportResponse, TransportError> + Send + Sync + 'static
+    {
+        InMemoryTransport {
+            handler: Box::new(handler),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Requests sent so far, in order.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<TransportRequest>> {
+        self.requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for InMemoryTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InMemoryTransport {{ requests: {} }}", self.lock().len())
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        let res = (self.handler)(&request);
+        self.lock().push(request);
+        Box::pin(async move { res })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryTransport, TransportError, TransportErrorKind, TransportResponse};
+    use crate::{
+        client::{EsClientBuilder, IndexPattern, Version},
+        errors::EsError,
+        retry::RetryPolicy,
+    };
+    use reqwest::{header::{HeaderMap, HeaderValue}, Method, StatusCode};
+    use serde_json::json;
+    use std::{io, sync::Arc, time::Duration};
+    use tokio::{net::{TcpSocket, TcpStream}, runtime::Runtime};
+
+    const COUNT: &str = r#"{"count": 3, "_shards": {"total": 1, "successful": 1, "skipped": 0, "failed": 0}}"#;
+
+    #[test]
+    fn send_with_in_memory_transport() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| Ok(TransportResponse::new(StatusCode::OK, COUNT))));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-opaque-id", HeaderValue::from_static("job-1"));
+        let client = EsClientBuilder::new()
+            .url("http://es-1:9200")
+            .headers(headers)
+            .timeout(Duration::from_secs(5))
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let res = rt.block_on(client.count(IndexPattern::index("test"), json!({"query": {"match_all": {}}}))).unwrap();
+        assert_eq!(res.count, 3);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method(), Method::POST);
+        assert_eq!(requests[0].url(), "http://es-1:9200/test/_count");
+        assert_eq!(requests[0].headers()["x-opaque-id"], "job-1");
+        assert_eq!(requests[0].headers()["content-type"], "application/json");
+        assert_eq!(requests[0].body(), Some(&br#"{"query":{"match_all":{}}}"#[..]));
+        assert_eq!(requests[0].timeout(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn failover_on_connect_error() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|request| match request.url().starts_with("http://es-1") {
+            true => Err(TransportError::new(TransportErrorKind::Connect, io::Error::from(io::ErrorKind::ConnectionRefused))),
+            false => Ok(TransportResponse::new(StatusCode::OK, COUNT)),
+        }));
+        let client = EsClientBuilder::new()
+            .nodes(&["http://es-1:9200", "http://es-2:9200"])
+            .version(Version::new(7, 10, 0))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let res = rt.block_on(client.send(Method::GET, "_cluster/health", &[("level", "indices")], None)).unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let urls: Vec<String> = transport.requests().iter().map(|request| request.url().to_owned()).collect();
+        assert_eq!(urls, vec![
+            "http://es-1:9200/_cluster/health?level=indices".to_owned(),
+            "http://es-2:9200/_cluster/health?level=indices".to_owned(),
+        ]);
+    }
+
+    #[test]
+    fn retry_and_fail_with_transport_error() {
+        let rt = Runtime::new().unwrap();
+        let transport = Arc::new(InMemoryTransport::new(|_| {
+            Err(TransportError::new(TransportErrorKind::Timeout, "no response"))
+        }));
+        let client = EsClientBuilder::new()
+            .version(Version::new(7, 10, 0))
+            .retry_policy(RetryPolicy::new().max_retries(2).initial_backoff(Duration::from_millis(1)))
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        match rt.block_on(client.send(Method::GET, "_cluster/health", &[], None)) {
+            Err(EsError::Timeout(error)) => assert_eq!(error.to_string(), "no response"),
+            res => panic!("Unexpected response: {:?}", res),
+        }
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn classify_connect_timeout() {
+        let rt = Runtime::new().unwrap();
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        // A listener that never accepts drops new connections once its backlog is full, so
+        // connecting to it times out.
+        let error = rt.block_on(async {
+            let socket = TcpSocket::new_v4().unwrap();
+            socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+            let listener = socket.listen(1).unwrap();
+            let address = listener.local_addr().unwrap();
+
+            let mut backlog = Vec::new();
+            for _ in 0..8 {
+                if let Ok(Ok(stream)) = tokio::time::timeout(Duration::from_millis(50), TcpStream::connect(address)).await {
+                    backlog.push(stream);
+                }
+            }
+            client.get(format!("http://{}/", address)).send().await.unwrap_err()
+        });
+        assert!(error.is_timeout());
+        assert_eq!(TransportError::from(error).kind(), TransportErrorKind::Connect);
+    }
+}
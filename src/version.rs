@@ -0,0 +1,183 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+
+use crate::errors::EsError;
+use crate::info::EsInfo;
+
+/// Major, minor and patch numbers at the start of a version number.
+static VERSION_NUMBER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+)\.(\d+)\.(\d+)").expect("Version regex is valid"));
+
+/// Distribution of the cluster the client is connected to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    Elasticsearch,
+    OpenSearch,
+}
+
+/// Semantic version of the cluster the client is connected to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    pub build_flavor: Option<String>,
+    pub distribution: Distribution,
+}
+
+impl Version {
+    /// Create new Elasticsearch Version.
+    pub fn new(major: u16, minor: u16, patch: u16) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+            build_flavor: None,
+            distribution: Distribution::Elasticsearch,
+        }
+    }
+
+    /// Create new OpenSearch Version.
+    pub fn opensearch(major: u16, minor: u16, patch: u16) -> Version {
+        Version {
+            distribution: Distribution::OpenSearch,
+            ..Version::new(major, minor, patch)
+        }
+    }
+
+    /// Parse an Elasticsearch version number such as `7.10.2` or `8.0.0-rc1`.
+    pub fn parse(number: &str) -> Result<Version, EsError> {
+        let caps = VERSION_NUMBER.captures(number)
+            .ok_or_else(|| EsError::VersionUnsupported(number.to_owned()))?;
+
+        // Each group only matches digits, so parsing only fails if the number overflows.
+        let part = |idx: usize| caps[idx].parse::<u16>()
+            .map_err(|_| EsError::VersionUnsupported(number.to_owned()));
+
+        Ok(Version::new(part(1)?, part(2)?, part(3)?))
+    }
+
+    /// Create the Version reported by the info request, failing if it is not supported.
+    pub fn from_info(info: &EsInfo) -> Result<Version, EsError> {
+        let mut version = Version::parse(&info.version.number)?;
+        version.build_flavor = info.version.build_flavor.clone();
+        if info.version.distribution.as_deref() == Some("opensearch") {
+            version.distribution = Distribution::OpenSearch;
+        }
+
+        match version.is_supported() {
+            true => Ok(version),
+            false => Err(EsError::VersionUnsupported(version.to_string())),
+        }
+    }
+
+    /// Whether document types can be used in request paths, removed in Elasticsearch 8.x
+    /// and OpenSearch 2.x.
+    pub fn supports_types(&self) -> bool {
+        match self.distribution {
+            Distribution::Elasticsearch => self.major < 8,
+            Distribution::OpenSearch => self.major < 2,
+        }
+    }
+
+    /// Whether the typeless document endpoints (`_doc`, `_create`, `_update`) are available,
+    /// added in Elasticsearch 7.x.
+    pub fn is_typeless(&self) -> bool {
+        match self.distribution {
+            Distribution::Elasticsearch => self.major >= 7,
+            Distribution::OpenSearch => true,
+        }
+    }
+
+    /// Whether the client supports this version, Elasticsearch 5.x to 8.x or OpenSearch 1.x to 2.x.
+    pub fn is_supported(&self) -> bool {
+        match self.distribution {
+            Distribution::Elasticsearch => (5..=8).contains(&self.major),
+            Distribution::OpenSearch => (1..=2).contains(&self.major),
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Distribution::Elasticsearch => write!(f, "Elasticsearch"),
+            Distribution::OpenSearch => write!(f, "OpenSearch"),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}.{}.{}", self.distribution, self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Distribution, Version};
+    use crate::info::EsInfo;
+
+    fn info(number: &str, distribution: Option<&str>) -> EsInfo {
+        let distribution = match distribution {
+            Some(distribution) => format!(r#""distribution": "{}","#, distribution),
+            None => String::new(),
+        };
+        serde_json::from_str(&format!(r#"{{
+            "name": "node-1",
+            "cluster_name": "docker-cluster",
+            "cluster_uuid": "HjwlCaVKQo2766zcX_l7DQ",
+            "version": {{
+                {}
+                "number": "{}",
+                "build_type": "docker",
+                "build_hash": "3d9f765",
+                "build_date": "2019-12-13T17:11:52.013738Z",
+                "build_snapshot": false,
+                "lucene_version": "9.7.0",
+                "minimum_wire_compatibility_version": "7.10.0",
+                "minimum_index_compatibility_version": "7.0.0"
+            }},
+            "tagline": "You Know, for Search"
+        }}"#, distribution, number)).unwrap()
+    }
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(Version::parse("6.8.6").unwrap(), Version::new(6, 8, 6));
+        assert_eq!(Version::parse("7.10.2").unwrap(), Version::new(7, 10, 2));
+        assert_eq!(Version::parse("8.0.0-rc1").unwrap(), Version::new(8, 0, 0));
+        assert!(Version::parse("8.x").is_err());
+    }
+
+    #[test]
+    fn version_from_es8_info() {
+        let version = Version::from_info(&info("8.11.3", None)).unwrap();
+        assert_eq!(version, Version::new(8, 11, 3));
+        assert_eq!(version.to_string(), "Elasticsearch 8.11.3");
+    }
+
+    #[test]
+    fn version_from_opensearch_info() {
+        let version = Version::from_info(&info("2.11.0", Some("opensearch"))).unwrap();
+        assert_eq!(version.distribution, Distribution::OpenSearch);
+        assert_eq!(version, Version::opensearch(2, 11, 0));
+    }
+
+    #[test]
+    fn version_type_support() {
+        assert!(Version::new(6, 8, 6).supports_types());
+        assert!(!Version::new(6, 8, 6).is_typeless());
+        assert!(Version::new(7, 10, 2).supports_types());
+        assert!(Version::new(7, 10, 2).is_typeless());
+        assert!(!Version::new(8, 0, 0).supports_types());
+        assert!(Version::opensearch(1, 3, 0).supports_types());
+        assert!(!Version::opensearch(2, 0, 0).supports_types());
+    }
+
+    #[test]
+    fn unsupported_version_from_info() {
+        assert!(Version::from_info(&info("2.4.6", None)).is_err());
+        assert!(Version::from_info(&info("3.0.0", Some("opensearch"))).is_err());
+    }
+}